@@ -0,0 +1,808 @@
+use crate::model::{self, BuilderField, Collection, ContainerOptions, FieldType, Pattern, Pointer};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Ident, Result, Type};
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let (options, fields) = model::parse(&input)?;
+    let cx = Context::new(&options);
+    let vis = options.vis;
+    let original_ident = options.ident;
+    let builder_ident = Ident::new(&format!("{}Builder", original_ident), original_ident.span());
+    let core = &cx.core;
+    let generics = options.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_defines = generate_builder_field_defines(&fields, &cx, options.serde);
+    let field_inits = generate_builder_field_inits(&fields, &cx);
+    let field_setter_funtions = generate_builder_setter_functions(&fields, &cx);
+    let build_function = generate_builder_build_function(&fields, &cx, true);
+    let error_ty = cx.error_type();
+    let error_define = cx.generate_error_define();
+    let serde_derive = if options.serde {
+        quote!(#[derive(::serde::Deserialize, ::serde::Serialize)])
+    } else {
+        TokenStream2::new()
+    };
+
+    let mut ret = quote! {
+        #error_define
+
+        #serde_derive
+        #vis struct #builder_ident #generics #where_clause {
+            #field_defines
+        }
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #field_setter_funtions
+
+            #build_function
+        }
+
+        impl #impl_generics #original_ident #ty_generics #where_clause {
+            #vis fn builder() -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #field_inits
+                }
+            }
+
+            #vis fn build_with<F>(f: F) -> #core::result::Result<Self, #error_ty>
+            where
+                F: #core::ops::FnOnce(&mut #builder_ident #ty_generics),
+            {
+                let mut builder = Self::builder();
+                f(&mut builder);
+                builder.build()
+            }
+
+            #vis fn try_build_from_fn<F>(f: F) -> #core::result::Result<Self, #error_ty>
+            where
+                F: #core::ops::FnOnce(&mut #builder_ident #ty_generics) -> #core::result::Result<(), #error_ty>,
+            {
+                let mut builder = Self::builder();
+                f(&mut builder)?;
+                builder.build()
+            }
+        }
+    };
+
+    if options.constructor_args {
+        ret.extend(generate_args_builder(&fields, &cx));
+    }
+    if options.args {
+        ret.extend(generate_args_parser(&fields, &cx));
+    }
+
+    Ok(ret)
+}
+
+/// `#[builder(constructor_args)]`: `Name::builder_with(..)` takes every required
+/// field positionally and returns a `NameArgsBuilder` whose `build()` cannot
+/// fail, since nothing is left to check, unless a `default_with` provider can.
+fn generate_args_builder(fields: &[BuilderField], cx: &Context) -> TokenStream2 {
+    let vis = cx.options.vis;
+    let original_ident = cx.options.ident;
+    let builder_ident = Ident::new(&format!("{}ArgsBuilder", original_ident), original_ident.span());
+    let generics = cx.options.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // 先按 arg_order 排序，未指定的保持声明顺序排在后面
+    let mut positional: Vec<_> = fields.iter()
+        .filter(|f| matches!(f.field_type, FieldType::Plain))
+        .collect();
+    positional.sort_by_key(|f| f.arg_order.as_ref().map(|lit| lit.base10_parse::<usize>().unwrap()).unwrap_or(usize::MAX));
+
+    let mut params = TokenStream2::new();
+    for field in &positional {
+        let ident = field.ident;
+        let ty = field.ty;
+        let arg_ty = match field.wrap {
+            Some(pointer) => wrapped_setter(pointer, ident, cx).0,
+            None => quote!(#ty),
+        };
+        params.extend(quote!(#ident: #arg_ty,));
+    }
+
+    let fields: Vec<_> = fields.iter()
+        .map(|f| match f.field_type {
+            FieldType::Plain => f.to_positional(),
+            _ => f.clone(),
+        })
+        .collect();
+    let field_defines = generate_builder_field_defines(&fields, cx, false);
+    let field_inits = generate_builder_field_inits(&fields, cx);
+    let field_setter_funtions = generate_builder_setter_functions(&fields, cx);
+    let fallible = fields.iter().any(|f| matches!(f.field_type, FieldType::Defaulted { .. }));
+    let build_function = generate_builder_build_function(&fields, cx, fallible);
+
+    quote! {
+        #vis struct #builder_ident #generics #where_clause {
+            #field_defines
+        }
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #field_setter_funtions
+
+            #build_function
+        }
+
+        impl #impl_generics #original_ident #ty_generics #where_clause {
+            #vis fn builder_with(#params) -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #field_inits
+                }
+            }
+        }
+    }
+}
+
+/// `#[builder(args)]`: `NameBuilder::from_args(..)` fills a builder from
+/// command line arguments and `NameBuilder::help()` describes them.
+///
+///   - `--field value` / `--field=value` parse the value with `FromStr`
+///   - `--each value` may repeat, `each` fields over maps take `--each key=value`
+///   - bool fields are flags: `--verbose` alone means `true`
+///   - `--help` fails with the help text as the error message
+fn generate_args_parser(fields: &[BuilderField], cx: &Context) -> TokenStream2 {
+    let vis = cx.options.vis;
+    let original_ident = cx.options.ident;
+    let builder_ident = Ident::new(&format!("{}Builder", original_ident), original_ident.span());
+    let (impl_generics, ty_generics, where_clause) = cx.options.generics.split_for_impl();
+
+    let mut options = Vec::new();
+    let mut arms = TokenStream2::new();
+    let mut flag_inits = TokenStream2::new();
+    for field in fields {
+        let ident = field.ident;
+        let ty = field.ty;
+        let flag = format!("--{}", kebab_case(ident));
+        let value_name = kebab_case(ident).to_uppercase().replace('-', "_");
+        let required = matches!(field.field_type, FieldType::Plain);
+
+        // (help 中的写法, 解析并存入 builder 的代码)
+        let (usage, store) = match &field.field_type {
+            FieldType::Positional | FieldType::Marker => continue,
+            FieldType::Plain | FieldType::Defaulted { .. } if is_bool(ty) => {
+                // 命令行上不出现的开关就是 false
+                if required {
+                    flag_inits.extend(quote!(builder.#ident = std::option::Option::Some(false);));
+                }
+                let value = parse_flag();
+                (flag, quote!(builder.#ident = std::option::Option::Some(#value);))
+            }
+            FieldType::Optional { inner_ty: Some(inner_ty) } if is_bool(inner_ty) => {
+                let value = parse_flag();
+                (flag, quote!(builder.#ident = std::option::Option::Some(#value);))
+            }
+            FieldType::Plain | FieldType::Defaulted { .. } => {
+                let value = parse_value(quote!(value), ty);
+                let store = quote! {
+                    let value = take_value(&name, value, &mut args)?;
+                    builder.#ident = std::option::Option::Some(#value);
+                };
+                (format!("{} <{}>", flag, value_name), store)
+            }
+            FieldType::Optional { inner_ty } => {
+                let value = match inner_ty {
+                    Some(inner_ty) => parse_value(quote!(value), inner_ty),
+                    None => {
+                        let inner_ty: Type = syn::parse_quote_spanned!(ty.span()=> <#ty as std::iter::IntoIterator>::Item);
+                        parse_value(quote!(value), &inner_ty)
+                    }
+                };
+                let store = quote! {
+                    let value = take_value(&name, value, &mut args)?;
+                    builder.#ident = std::option::Option::Some(#value);
+                };
+                (format!("{} <{}>", flag, value_name), store)
+            }
+            FieldType::Repeated { each, collection, optional, .. } => {
+                let flag = format!("--{}", kebab_case(each));
+                let value_name = kebab_case(each).to_uppercase().replace('-', "_");
+                let target = if *optional {
+                    quote!(builder.#ident.get_or_insert_with(std::default::Default::default))
+                } else {
+                    quote!(builder.#ident)
+                };
+                match collection {
+                    Collection::Vec { elem_ty, .. } => {
+                        let value = parse_value(quote!(value), elem_ty);
+                        let store = quote! {
+                            let value = take_value(&name, value, &mut args)?;
+                            #target.push(#value);
+                        };
+                        (format!("{} <{}>...", flag, value_name), store)
+                    }
+                    Collection::Map { key_ty, value_ty, .. } => {
+                        let key = parse_value(quote!(key), key_ty);
+                        let value = parse_value(quote!(value), value_ty);
+                        let store = quote! {
+                            let value = take_value(&name, value, &mut args)?;
+                            let (key, value) = value.split_once('=')
+                                .ok_or_else(|| format!("`{}` expects KEY=VALUE, got `{}`", name, value))?;
+                            #target.insert(#key, #value);
+                        };
+                        (format!("{} <KEY=VALUE>...", flag), store)
+                    }
+                }
+            }
+        };
+
+        let mut description = field.doc.lines().collect::<Vec<_>>().join(" ");
+        if required && !is_bool(ty) {
+            description = format!("{} (required)", description).trim_start().to_owned();
+        }
+        let arm_flag = usage.split(' ').next().unwrap().to_owned();
+        arms.extend(quote! {
+            #arm_flag => { #store }
+        });
+        options.push((usage, description));
+    }
+    options.push(("--help".to_owned(), "Print this help".to_owned()));
+
+    let help = generate_help(&kebab_case(original_ident), &cx.options.doc, &options);
+    let error_ty = cx.error_type();
+
+    quote! {
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            /// Fills a builder from command line arguments, without the program
+            /// name. `--help` fails with the help text.
+            #vis fn from_args<I>(args: I) -> std::result::Result<Self, #error_ty>
+            where
+                I: std::iter::IntoIterator<Item = std::string::String>,
+            {
+                // 值可以写在 `=` 后面，也可以是下一个参数
+                fn take_value<I>(
+                    name: &str,
+                    value: std::option::Option<std::string::String>,
+                    args: &mut I,
+                ) -> std::result::Result<std::string::String, std::string::String>
+                where
+                    I: std::iter::Iterator<Item = std::string::String>,
+                {
+                    match value {
+                        std::option::Option::Some(value) => std::result::Result::Ok(value),
+                        std::option::Option::None => args.next().ok_or_else(|| format!("`{}` expects a value", name)),
+                    }
+                }
+
+                let mut builder = <#original_ident #ty_generics>::builder();
+                #flag_inits
+                let mut args = args.into_iter();
+                while let std::option::Option::Some(arg) = args.next() {
+                    let (name, value) = match arg.split_once('=') {
+                        std::option::Option::Some((name, value)) => (name.to_owned(), std::option::Option::Some(value.to_owned())),
+                        std::option::Option::None => (arg.clone(), std::option::Option::None),
+                    };
+                    match name.as_str() {
+                        #arms
+                        "--help" => return std::result::Result::Err(Self::help().into()),
+                        _ => return std::result::Result::Err(format!("unexpected argument `{}`", arg).into()),
+                    }
+                }
+                std::result::Result::Ok(builder)
+            }
+
+            #vis fn help() -> &'static str {
+                #help
+            }
+        }
+    }
+}
+
+/// `tokens` with every token moved to `span`, so that an error about the
+/// whole expression they start lands there too.
+fn respan(tokens: &TokenStream2, span: Span) -> TokenStream2 {
+    tokens.clone().into_iter().map(|mut token| {
+        token.set_span(span);
+        token
+    }).collect()
+}
+
+/// `field_name` / `FieldName` as `field-name`.
+fn kebab_case(ident: &Ident) -> String {
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#");
+    let mut ret = String::new();
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() {
+            if i > 0 {
+                ret.push('-');
+            }
+            ret.extend(ch.to_lowercase());
+        } else if ch == '_' {
+            ret.push('-');
+        } else {
+            ret.push(ch);
+        }
+    }
+    ret
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident("bool"))
+}
+
+/// Parses the string `src` as `ty`, the span of `ty` points at a missing
+/// `FromStr` impl.
+fn parse_value(src: TokenStream2, ty: &Type) -> TokenStream2 {
+    let parse = quote_spanned!(ty.span()=> #src.parse::<#ty>());
+    quote! {
+        #parse.map_err(|err| format!("invalid value `{}` for `{}`: {}", #src, name, err))?
+    }
+}
+
+fn parse_flag() -> TokenStream2 {
+    quote! {
+        match value {
+            std::option::Option::Some(value) => value.parse::<bool>()
+                .map_err(|err| format!("invalid value `{}` for `{}`: {}", value, name, err))?,
+            std::option::Option::None => true,
+        }
+    }
+}
+
+fn generate_help(name: &str, doc: &str, options: &[(String, String)]) -> String {
+    let width = options.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+    let mut help = format!("Usage: {} [OPTIONS]\n", name);
+    if !doc.is_empty() {
+        help.push_str(&format!("\n{}\n", doc));
+    }
+    help.push_str("\nOptions:\n");
+    for (usage, description) in options {
+        help.push_str(format!("  {:width$}  {}", usage, description, width = width).trim_end());
+        help.push('\n');
+    }
+    help
+}
+
+fn generate_builder_field_defines(fields: &[BuilderField], cx: &Context, serde: bool) -> TokenStream2 {
+    let core = &cx.core;
+    let alloc = &cx.alloc;
+    let mut stream = TokenStream2::new();
+
+    for field in fields {
+        let ident = field.ident;
+        let ty = field.ty;
+        if serde {
+            // 缺失的键保持未设置，序列化时也跳过未设置的字段，由 build() 报告缺少哪些必要字段
+            // 标记字段只有一个值，不参与序列化
+            let is_unset = match field.field_type {
+                FieldType::Repeated { optional: false, collection: Collection::Vec { .. }, .. } => {
+                    Some(quote!(#alloc::vec::Vec::is_empty).to_string())
+                }
+                FieldType::Repeated { optional: false, collection: Collection::Map { .. }, .. } => {
+                    Some(quote!(std::collections::HashMap::is_empty).to_string())
+                }
+                FieldType::Marker => None,
+                _ => Some(quote!(#core::option::Option::is_none).to_string()),
+            };
+            stream.extend(match is_unset {
+                Some(is_unset) => quote!(#[serde(default, skip_serializing_if = #is_unset)]),
+                None => quote!(#[serde(skip)]),
+            });
+        }
+        match field.field_type {
+            FieldType::Plain | FieldType::Defaulted { .. } => stream.extend(quote! {
+                #ident: #core::option::Option<#ty>,
+            }),
+            // 标记字段也要保留在 builder 中，泛型参数可能只出现在 PhantomData 里
+            FieldType::Positional | FieldType::Optional { .. } | FieldType::Repeated { .. } | FieldType::Marker => {
+                stream.extend(quote! {
+                    #ident: #ty,
+                })
+            }
+        }
+    }
+
+    stream
+}
+
+fn generate_builder_field_inits(fields: &[BuilderField], cx: &Context) -> TokenStream2 {
+    let core = &cx.core;
+    let mut stream = TokenStream2::new();
+
+    for field in fields {
+        let ident = field.ident;
+        match field.field_type {
+            FieldType::Repeated { .. } | FieldType::Marker => stream.extend(quote_spanned! {field.ty.span()=>
+                #ident: #core::default::Default::default(),
+            }),
+            FieldType::Plain | FieldType::Defaulted { .. } => stream.extend(quote! {
+                #ident: #core::option::Option::None,
+            }),
+            // 显式标注 optional 的字段不是 Option 时，错误指向字段类型
+            FieldType::Optional { .. } => {
+                let core = respan(core, field.ty.span());
+                stream.extend(quote_spanned! {field.ty.span()=>
+                    #ident: #core::option::Option::None,
+                })
+            }
+            FieldType::Positional => {
+                let value = match field.wrap {
+                    Some(pointer) => wrapped_setter(pointer, ident, cx).1,
+                    None => quote!(#ident),
+                };
+                stream.extend(quote! {
+                    #ident: #value,
+                });
+            }
+        }
+    }
+
+    stream
+}
+
+fn generate_builder_setter_functions(fields: &[BuilderField], cx: &Context) -> TokenStream2 {
+    let vis = cx.options.vis;
+    let core = &cx.core;
+    let mut stream = TokenStream2::new();
+
+    for field in fields {
+        let ident = field.ident;
+        let ty = field.ty;
+        // 返回 setter 的参数类型以及存入 builder 的值
+        let setter_argument = |value_ty: TokenStream2, arg: &Ident| match field.wrap {
+            Some(pointer) => wrapped_setter(pointer, arg, cx),
+            None => (value_ty, quote!(#arg)),
+        };
+
+        match &field.field_type {
+            FieldType::Positional | FieldType::Marker => {}
+            FieldType::Plain | FieldType::Defaulted { .. } => {
+                let (arg_ty, value) = setter_argument(quote!(#ty), ident);
+                stream.extend(quote! {
+                    #vis fn #ident(&mut self, #ident: #arg_ty) -> &mut Self {
+                        self.#ident = #core::option::Option::Some(#value);
+                        self
+                    }
+                });
+            }
+            FieldType::Optional { inner_ty } => {
+                // 显式标注的 optional 可能是类型别名，只能借助 Option<T>: IntoIterator<Item = T> 拿到内部类型
+                // 字段其实不是 Option 时，错误指向字段类型
+                let value_ty = match inner_ty {
+                    Some(inner_ty) => quote!(#inner_ty),
+                    None => quote_spanned!(ty.span()=> <#ty as #core::iter::IntoIterator>::Item),
+                };
+                let (arg_ty, value) = setter_argument(value_ty, ident);
+                let store = {
+                    let core = respan(core, ty.span());
+                    quote_spanned!(ty.span()=> #core::option::Option::Some(#value))
+                };
+                stream.extend(quote! {
+                    #vis fn #ident(&mut self, #ident: #arg_ty) -> &mut Self {
+                        self.#ident = #store;
+                        self
+                    }
+                });
+            }
+            FieldType::Repeated { each, collection, optional, .. } => {
+                let (Collection::Vec { ty: collection_ty, .. } | Collection::Map { ty: collection_ty, .. }) = collection;
+                let target = if *optional {
+                    // 第一次添加元素时把 None 变成 Some
+                    quote_spanned!(collection_ty.span()=> self.#ident.get_or_insert_with(#core::default::Default::default))
+                } else {
+                    quote!(self.#ident)
+                };
+                if each != ident {
+                    let store = if *optional {
+                        quote!(#core::option::Option::Some(#ident))
+                    } else {
+                        quote!(#ident)
+                    };
+                    stream.extend(quote! {
+                        #vis fn #ident(&mut self, #ident: #collection_ty) -> &mut Self {
+                            self.#ident = #store;
+                            self
+                        }
+                    });
+                }
+                match collection {
+                    Collection::Vec { elem_ty, .. } => {
+                        let (arg_ty, value) = setter_argument(quote!(#elem_ty), each);
+                        stream.extend(quote! {
+                            #vis fn #each(&mut self, #each: #arg_ty) -> &mut Self {
+                                #target.push(#value);
+                                self
+                            }
+                        });
+                    }
+                    Collection::Map { key_ty, value_ty, .. } => {
+                        let value_ident = Ident::new("value", each.span());
+                        let (arg_ty, value) = setter_argument(quote!(#value_ty), &value_ident);
+                        // 键类型缺少 Eq + Hash 时错误指向键类型
+                        let insert = quote_spanned!(key_ty.span()=> insert);
+                        stream.extend(quote! {
+                            #vis fn #each(&mut self, key: #key_ty, #value_ident: #arg_ty) -> &mut Self {
+                                #target.#insert(key, #value);
+                                self
+                            }
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    stream
+}
+
+fn generate_builder_build_function(fields: &[BuilderField], cx: &Context, fallible: bool) -> TokenStream2 {
+    let mut stream = generate_build(fields, cx, fallible, false);
+    if fields.iter().any(|f| matches!(f.field_type, FieldType::Defaulted { lazy: true, .. })) {
+        stream.extend(generate_build(fields, cx, fallible, true));
+    }
+    stream
+}
+
+/// `build()`, or with `is_async` a `build_async()` that also awaits the
+/// `lazy_default` providers. `build()` treats an unset lazily defaulted field
+/// as missing.
+fn generate_build(fields: &[BuilderField], cx: &Context, fallible: bool, is_async: bool) -> TokenStream2 {
+    let vis = cx.options.vis;
+    let original_ident = cx.options.ident;
+    let (_, ty_generics, _) = cx.options.generics.split_for_impl();
+    let core = &cx.core;
+    let mut check_stream = TokenStream2::new();
+    let mut default_stream = TokenStream2::new();
+    let mut init_stream = TokenStream2::new();
+
+    // clone 模式保留 builder 中的值；take 模式把值移出，字段不需要实现 Clone。
+    // 两种模式都先检查完所有必要字段、算出所有默认值再取值，失败时 builder 保持不变
+    let take = cx.options.pattern == Pattern::Take;
+    // 反序列化的配置文档可能缺好几个键，serde builder 一次报告全部
+    let collect_missing = cx.options.serde && !cx.options.no_std;
+    let missing = Ident::new("missing", Span::mixed_site());
+    for field in fields {
+        let ident = field.ident;
+        let ty = field.ty;
+        // 字段类型缺少 Clone、Default 等 trait 时，错误指向字段类型而不是 derive
+        let span = ty.span();
+        let field_type = match &field.field_type {
+            FieldType::Defaulted { lazy: true, .. } if !is_async => &FieldType::Plain,
+            field_type => field_type,
+        };
+        // clone 模式下每个字段恰好克隆一次，且都经过 __clone_field::<字段类型>，
+        // 缺少 Clone 时每个字段只报一个错误
+        let clone = |field: TokenStream2| quote_spanned!(span=> __clone_field::<#ty>(#field));
+        let value = match (field_type, take) {
+            (FieldType::Repeated { default_empty: true, .. }, false) => {
+                let value = clone(quote!(&self.#ident));
+                quote_spanned!(span=> #core::option::Option::Some(#value.unwrap_or_default()))
+            }
+            (FieldType::Repeated { default_empty: true, .. }, true) => {
+                quote_spanned!(span=> #core::option::Option::Some(self.#ident.take().unwrap_or_default()))
+            }
+            (FieldType::Positional | FieldType::Optional { .. } | FieldType::Repeated { .. }, false) => {
+                clone(quote!(&self.#ident))
+            }
+            (FieldType::Plain, false) => clone(quote!(self.#ident.as_ref().unwrap())),
+            (FieldType::Plain, true) => quote!(self.#ident.take().unwrap()),
+            (FieldType::Defaulted { provider, lazy }, _) => {
+                // 默认值存放在宏卫生的局部变量中，不会遮蔽用户的同名函数
+                let default = Ident::new(&ident.to_string(), Span::mixed_site());
+                let await_ = if *lazy { quote!(.await) } else { TokenStream2::new() };
+                let error = cx.default_error(ident);
+                // 返回类型不对时错误指向 default_with 的字符串
+                let call = quote_spanned!(provider.span()=> #provider()#await_ #error?);
+                default_stream.extend(quote! {
+                    let #default: #core::option::Option<#ty> = if self.#ident.is_none() {
+                        #core::option::Option::Some(#call)
+                    } else {
+                        #core::option::Option::None
+                    };
+                });
+                let stored = if take {
+                    quote!(self.#ident.take().unwrap())
+                } else {
+                    clone(quote!(self.#ident.as_ref().unwrap()))
+                };
+                quote! {
+                    match #default {
+                        #core::option::Option::Some(value) => value,
+                        #core::option::Option::None => #stored,
+                    }
+                }
+            }
+            (FieldType::Optional { .. }, true) => quote_spanned!(span=> self.#ident.take()),
+            (FieldType::Repeated { optional: true, .. }, true) => quote!(self.#ident.take()),
+            (FieldType::Repeated { .. }, true) => quote_spanned!(span=> #core::mem::take(&mut self.#ident)),
+            (FieldType::Marker, _) => quote_spanned!(span=> #core::default::Default::default()),
+            (FieldType::Positional, true) => unreachable!("constructor_args requires the clone pattern"),
+        };
+        init_stream.extend(quote! {
+            #ident: #value,
+        });
+
+        if let FieldType::Plain = field_type {
+            let check = if collect_missing {
+                quote!(#missing.push(stringify!(#ident));)
+            } else {
+                let error = cx.missing_field_error(ident);
+                quote!(return #core::result::Result::Err(#error))
+            };
+            check_stream.extend(quote! {
+                if self.#ident.is_none() {
+                    #check
+                }
+            });
+        }
+    }
+    if collect_missing && !check_stream.is_empty() {
+        check_stream = quote! {
+            let mut #missing = std::vec::Vec::<&'static str>::new();
+            #check_stream
+            match #missing.as_slice() {
+                [] => {}
+                [field] => return std::result::Result::Err(format!("{} field missing", field).into()),
+                fields => return std::result::Result::Err(format!("{} fields missing", fields.join(", ")).into()),
+            }
+        };
+    }
+
+    let clone_field = if take {
+        TokenStream2::new()
+    } else {
+        quote! {
+            fn __clone_field<T: #core::clone::Clone>(field: &T) -> T {
+                field.clone()
+            }
+        }
+    };
+
+    let receiver = if take { quote!(&mut self) } else { quote!(&self) };
+    let (asyncness, name) = if is_async {
+        (quote!(async), Ident::new("build_async", Span::call_site()))
+    } else {
+        (TokenStream2::new(), Ident::new("build", Span::call_site()))
+    };
+
+    if !fallible {
+        return quote! {
+            #vis #asyncness fn #name(#receiver) -> #original_ident #ty_generics {
+                #clone_field
+
+                #original_ident {
+                    #init_stream
+                }
+            }
+        };
+    }
+
+    let error_ty = cx.error_type();
+    quote! {
+        #vis #asyncness fn #name(#receiver) -> #core::result::Result<#original_ident #ty_generics, #error_ty> {
+            #clone_field
+            #check_stream
+            #default_stream
+
+            #core::result::Result::Ok(
+                #original_ident {
+                    #init_stream
+                }
+            )
+        }
+    }
+}
+
+/// Names shared by everything the derive generates. With `#[builder(no_std)]`
+/// paths go through `::core` and `::alloc`, and `build()` reports a generated
+/// `NameBuilderError` that does not allocate.
+struct Context<'a> {
+    options: &'a ContainerOptions<'a>,
+    core: TokenStream2,
+    alloc: TokenStream2,
+}
+
+impl<'a> Context<'a> {
+    fn new(options: &'a ContainerOptions<'a>) -> Self {
+        let (core, alloc) = if options.no_std {
+            (quote!(::core), quote!(::alloc))
+        } else {
+            (quote!(std), quote!(std))
+        };
+        Context { options, core, alloc }
+    }
+
+    fn error_ident(&self) -> Ident {
+        let original_ident = self.options.ident;
+        Ident::new(&format!("{}BuilderError", original_ident), original_ident.span())
+    }
+
+    /// Error type of `build()`, shared by every generated function that builds.
+    fn error_type(&self) -> TokenStream2 {
+        if self.options.no_std {
+            let error_ident = self.error_ident();
+            quote!(#error_ident)
+        } else {
+            quote!(std::boxed::Box<dyn std::error::Error>)
+        }
+    }
+
+    fn missing_field_error(&self, ident: &Ident) -> TokenStream2 {
+        if self.options.no_std {
+            let error_ident = self.error_ident();
+            quote!(#error_ident::MissingField(stringify!(#ident)))
+        } else {
+            quote!(format!("{} field missing", stringify!(#ident)).into())
+        }
+    }
+
+    /// Converts the error of a `default_with` provider: `?` does it for the
+    /// boxed error, the generated error only records the field.
+    fn default_error(&self, ident: &Ident) -> TokenStream2 {
+        if self.options.no_std {
+            let error_ident = self.error_ident();
+            quote!(.map_err(|_| #error_ident::DefaultFailed(stringify!(#ident))))
+        } else {
+            TokenStream2::new()
+        }
+    }
+
+    fn generate_error_define(&self) -> TokenStream2 {
+        if !self.options.no_std {
+            return TokenStream2::new();
+        }
+
+        let vis = self.options.vis;
+        let core = &self.core;
+        let error_ident = self.error_ident();
+        quote! {
+            #[derive(#core::fmt::Debug, #core::clone::Clone, #core::marker::Copy, #core::cmp::PartialEq, #core::cmp::Eq)]
+            #vis enum #error_ident {
+                MissingField(&'static str),
+                DefaultFailed(&'static str),
+            }
+
+            impl #core::fmt::Display for #error_ident {
+                fn fmt(&self, f: &mut #core::fmt::Formatter<'_>) -> #core::fmt::Result {
+                    match self {
+                        #error_ident::MissingField(field) => #core::write!(f, "{} field missing", field),
+                        #error_ident::DefaultFailed(field) => #core::write!(f, "failed to compute default for {}", field),
+                    }
+                }
+            }
+
+            impl #core::error::Error for #error_ident {}
+        }
+    }
+}
+
+/// Argument type and wrapping expression of a `setter(wrap)` setter whose
+/// argument `arg` ends up in `pointer`.
+///
+///   - `Box<dyn Trait>` takes `impl Trait + 'static` and boxes it
+///   - `Rc<str>` / `Arc<[T]>` take `&str` / `&[T]` and copy from it
+///   - `Box<T>` / `Rc<T>` / `Arc<T>` take `T`
+///   - `Cow<'a, B>` takes `impl Into<Cow<'a, B>>`
+fn wrapped_setter(pointer: Pointer, arg: &Ident, cx: &Context) -> (TokenStream2, TokenStream2) {
+    let core = &cx.core;
+    let alloc = &cx.alloc;
+    let (pointer, inner_ty) = match pointer {
+        Pointer::Cow(ty) => {
+            return (quote!(impl #core::convert::Into<#ty>), quote!(#core::convert::Into::into(#arg)));
+        }
+        Pointer::Box(inner_ty) => (quote!(#alloc::boxed::Box), inner_ty),
+        Pointer::Rc(inner_ty) => (quote!(#alloc::rc::Rc), inner_ty),
+        Pointer::Arc(inner_ty) => (quote!(#alloc::sync::Arc), inner_ty),
+    };
+
+    match inner_ty {
+        syn::Type::TraitObject(syn::TypeTraitObject { bounds, .. }) => {
+            // Box<dyn Trait> 省略的生命周期默认为 'static
+            let lifetime = if bounds.iter().any(|bound| matches!(bound, syn::TypeParamBound::Lifetime(_))) {
+                None
+            } else {
+                Some(quote!(+ 'static))
+            };
+            (quote!(impl #bounds #lifetime), quote!(#pointer::new(#arg)))
+        }
+        syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => {
+            (quote!(&#inner_ty), quote!(#pointer::from(#arg)))
+        }
+        syn::Type::Slice(_) => (quote!(&#inner_ty), quote!(#pointer::from(#arg))),
+        _ => (quote!(#inner_ty), quote!(#pointer::new(#arg))),
+    }
+}
@@ -0,0 +1,636 @@
+//! Intermediate model of a `#[derive(Builder)]` input: container options and a
+//! typed classification of every field. `expand` renders the builder from it.
+
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericArgument, Generics, Ident, LitInt, LitStr, Path,
+    PathArguments, Result, Type, TypePath, Visibility,
+};
+
+/// How `build()` gets the values out of the builder.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// `build(&self)` clones every field, the builder can be reused.
+    Clone,
+    /// `build(&mut self)` moves the fields out and leaves the builder empty,
+    /// so fields don't have to be `Clone`.
+    Take,
+}
+
+pub struct ContainerOptions<'a> {
+    pub vis: &'a Visibility,
+    pub ident: &'a Ident,
+    pub generics: &'a Generics,
+    pub pattern: Pattern,
+    pub constructor_args: bool,
+    pub no_std: bool,
+    pub serde: bool,
+    /// `#[builder(args)]`: generate `from_args` and `help` on the builder.
+    pub args: bool,
+    /// Doc comment of the struct, the description in `--help`.
+    pub doc: String,
+}
+
+#[derive(Clone)]
+pub struct BuilderField<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    pub field_type: FieldType<'a>,
+    /// `#[builder(setter(wrap))]`: the setter takes the pointee and does the
+    /// wrapping itself.
+    pub wrap: Option<Pointer<'a>>,
+    /// `#[builder(arg_order = N)]`: position among the `builder_with` arguments.
+    pub arg_order: Option<LitInt>,
+    /// Doc comment of the field, shown next to its option in `--help`.
+    pub doc: String,
+}
+
+/// How a single field is stored in the builder and turned back into the
+/// original struct.
+#[derive(Clone)]
+pub enum FieldType<'a> {
+    /// Stored as `Option<T>`, `build()` fails while it is still unset.
+    Plain,
+    /// A required field passed to `builder_with`, stored as is without a setter.
+    Positional,
+    /// The field itself is an `Option`, the setter takes `inner_ty` and wraps
+    /// it in `Some`. `inner_ty` is unknown for aliases marked
+    /// `#[builder(optional)]`.
+    Optional { inner_ty: Option<&'a Type> },
+    /// A collection filled one element at a time through a setter named
+    /// `each`; the all-at-once setter is kept unless it would clash with
+    /// `each`. With `optional` the field is an `Option` of the collection that
+    /// the first `each` call turns into `Some`, and `default_empty` makes an
+    /// untouched field build to `Some` of an empty collection instead of `None`.
+    Repeated { each: Ident, collection: Collection<'a>, optional: bool, default_empty: bool },
+    /// Stored as `Option<T>` like `Plain`, but `build()` calls `provider`, a
+    /// `fn() -> Result<T, E>`, instead of failing while it is unset. With
+    /// `lazy` the provider is an `async fn` that only `build_async` awaits.
+    Defaulted { provider: Path, lazy: bool },
+    /// `PhantomData<T>`, `PhantomPinned` or `()`: there is only one value, so
+    /// the field is filled with `Default::default()` and gets no setter.
+    Marker,
+}
+
+/// Collections supported by `#[builder(each = "...")]`.
+#[derive(Clone, Copy)]
+pub enum Collection<'a> {
+    /// `Vec<T>`, one `T` per call.
+    Vec { ty: &'a Type, elem_ty: &'a Type },
+    /// `HashMap<K, V>`, one key and value per call.
+    Map { ty: &'a Type, key_ty: &'a Type, value_ty: &'a Type },
+}
+
+/// Smart pointers understood by `#[builder(setter(wrap))]`, with their pointee.
+#[derive(Clone, Copy)]
+pub enum Pointer<'a> {
+    Box(&'a Type),
+    Rc(&'a Type),
+    Arc(&'a Type),
+    /// The whole `Cow<'a, B>` type, built with `Into`.
+    Cow(&'a Type),
+}
+
+impl BuilderField<'_> {
+    /// The same field as a positional `builder_with` argument.
+    pub fn to_positional(&self) -> Self {
+        BuilderField { field_type: FieldType::Positional, ..self.clone() }
+    }
+}
+
+/// Parses the whole input, reporting every attribute problem at once.
+pub fn parse(input: &DeriveInput) -> Result<(ContainerOptions<'_>, Vec<BuilderField<'_>>)> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => &fields.named,
+            fields => return Err(Error::new_spanned(fields, "`derive(Builder)` only supports structs with named fields")),
+        },
+        Data::Enum(e) => return Err(Error::new_spanned(e.enum_token, "`derive(Builder)` only supports structs with named fields")),
+        Data::Union(u) => return Err(Error::new_spanned(u.union_token, "`derive(Builder)` only supports structs with named fields")),
+    };
+
+    let mut errors = Errors::default();
+    let options = parse_container_options(input, &mut errors);
+
+    let field_names: Vec<_> = fields.iter()
+        .filter_map(|f| f.ident.as_ref())
+        .map(|ident| ident.to_string())
+        .collect();
+    let mut builder_fields = Vec::new();
+    for field in fields {
+        match classify_field(field, &field_names, &mut errors) {
+            Ok(builder_field) => builder_fields.push(builder_field),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    check_arg_orders(&builder_fields, &options, &mut errors);
+    if options.args {
+        check_help_flag(&builder_fields, &mut errors);
+    }
+    errors.finish()?;
+
+    Ok((options, builder_fields))
+}
+
+// 要求：
+//     1. 必要字段用 Option 包裹，在最后 build 的时候验证不为 None
+//     2. 可选字段本身就是 Option<T> 类型，不需要再用 Option 包裹
+//     3. Vec<T> 类型字段可用 #[builder(each = "...")] 指定添加一次一个的 setter 函数
+//        在其他类型上使用 each 在字段类型位置报错
+//     4. #[builder(optional)] / #[builder(required)] 覆盖对 Option 的自动识别
+//     5. PhantomData、PhantomPinned 和 () 字段自动初始化，不生成 setter
+//     6. #[builder(default_with = "...")] 的字段未设置时由函数计算默认值
+// 为了标识字段属于哪一种情况，使用 FieldType 枚举 [Plain(1), Optional(2), Repeated(3), Marker(5), Defaulted(6)]
+fn classify_field<'a>(field: &'a Field, field_names: &[String], errors: &mut Errors) -> Result<BuilderField<'a>> {
+    let ident = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+    let attrs = parse_field_attrs(field, errors);
+
+    let field_type = if let Some(each) = attrs.each {
+        // 情况 3 ---- Inert Attribute，只允许 Vec<T>、HashMap<K, V> 或者它们的 Option
+        let (optional, collection_ty) = match get_std_type_argument(ty, OPTION_PATHS) {
+            Some(inner_ty) => (true, inner_ty),
+            None => (false, ty),
+        };
+        let collection = match get_collection(collection_ty) {
+            Some(collection) => collection,
+            None => {
+                return Err(Error::new_spanned(ty, r#"`builder(each = "...")` requires a `Vec<T>` or `HashMap<K, V>` field, optionally wrapped in `Option`"#));
+            }
+        };
+        if attrs.optional.is_some() || attrs.required.is_some() || attrs.default_with.is_some() {
+            return Err(Error::new_spanned(&each, "`each` cannot be combined with `optional`, `required` or `default_with`"));
+        }
+        if let (Some(default_empty), false) = (&attrs.default_empty, optional) {
+            return Err(Error::new_spanned(default_empty, "`default_empty` only applies to `Option` collections"));
+        }
+        // 与其它字段同名的 each 会生成重名的 setter，这种情况退化为普通字段
+        if each != *ident && field_names.contains(&each.to_string()) {
+            FieldType::Plain
+        } else {
+            FieldType::Repeated { each, collection, optional, default_empty: attrs.default_empty.is_some() }
+        }
+    } else {
+        if let Some(default_empty) = &attrs.default_empty {
+            return Err(Error::new_spanned(default_empty, r#"`default_empty` requires `builder(each = "...")`"#));
+        }
+        if let (Some(lazy_default), None) = (&attrs.lazy_default, &attrs.default_with) {
+            return Err(Error::new_spanned(lazy_default, r#"`lazy_default` requires `default_with = "..."`"#));
+        }
+        match (attrs.optional, attrs.required) {
+            (Some(_), Some(required)) => {
+                return Err(Error::new_spanned(required, "`optional` and `required` are mutually exclusive"));
+            }
+            // 情况 6 ---- 与 required 一样覆盖对 Option 的自动识别
+            (optional, required) if attrs.default_with.is_some() => {
+                if let Some(path) = optional.or(required) {
+                    let message = format!("`{}` cannot be combined with `default_with`", quote!(#path));
+                    return Err(Error::new_spanned(path, message));
+                }
+                FieldType::Defaulted { provider: attrs.default_with.unwrap(), lazy: attrs.lazy_default.is_some() }
+            }
+            (None, Some(_)) => FieldType::Plain,
+            // 情况 2 ---- Option，显式标注时允许类型别名
+            (Some(_), None) => FieldType::Optional { inner_ty: get_std_type_argument(ty, OPTION_PATHS) },
+            // 情况 5 ---- 没有任何标注时才识别，setter(wrap) 等属性对它们没有意义
+            (None, None) if is_marker(ty) && attrs.wrap.is_none() && attrs.arg_order.is_none() => FieldType::Marker,
+            (None, None) => match get_std_type_argument(ty, OPTION_PATHS) {
+                Some(inner_ty) => FieldType::Optional { inner_ty: Some(inner_ty) },
+                // 其它都算是情况 1
+                None => FieldType::Plain,
+            },
+        }
+    };
+
+    let wrap = match &attrs.wrap {
+        None => None,
+        Some(wrap) => {
+            let wrapped_ty = match &field_type {
+                FieldType::Plain | FieldType::Positional | FieldType::Defaulted { .. } => Some(ty),
+                FieldType::Optional { inner_ty } => *inner_ty,
+                FieldType::Repeated { collection: Collection::Vec { elem_ty, .. }, .. } => Some(*elem_ty),
+                FieldType::Repeated { collection: Collection::Map { value_ty, .. }, .. } => Some(*value_ty),
+                FieldType::Marker => unreachable!("fields with `setter(wrap)` are never markers"),
+            };
+            match wrapped_ty {
+                Some(wrapped_ty) => match get_pointer(wrapped_ty) {
+                    Some(pointer) => Some(pointer),
+                    None => {
+                        return Err(Error::new_spanned(wrapped_ty, "`setter(wrap)` requires a `Box`, `Rc`, `Arc` or `Cow` type"));
+                    }
+                },
+                None => return Err(Error::new_spanned(wrap, "`setter(wrap)` cannot see through a type alias")),
+            }
+        }
+    };
+
+    if let Some(arg_order) = &attrs.arg_order {
+        if !matches!(field_type, FieldType::Plain) {
+            return Err(Error::new_spanned(arg_order, "`arg_order` only applies to required fields"));
+        }
+    }
+
+    Ok(BuilderField { ident, ty, field_type, wrap, arg_order: attrs.arg_order, doc: doc_comment(&field.attrs) })
+}
+
+/// `from_args` always handles `--help` itself and `help()` sits next to the
+/// setters, so no field may take over that name.
+fn check_help_flag(fields: &[BuilderField], errors: &mut Errors) {
+    for field in fields {
+        let each = match &field.field_type {
+            FieldType::Positional | FieldType::Marker => continue,
+            FieldType::Repeated { each, .. } => Some(each),
+            _ => None,
+        };
+        if let Some(name) = std::iter::once(field.ident).chain(each).find(|name| *name == "help") {
+            errors.push(Error::new_spanned(name, "`help` is reserved for the generated `--help` with `#[builder(args)]`"));
+        }
+    }
+}
+
+fn check_arg_orders(fields: &[BuilderField], options: &ContainerOptions, errors: &mut Errors) {
+    let mut seen = Vec::new();
+    for arg_order in fields.iter().filter_map(|f| f.arg_order.as_ref()) {
+        if !options.constructor_args {
+            errors.push(Error::new_spanned(arg_order, "`arg_order` requires `#[builder(constructor_args)]` on the struct"));
+        } else if seen.contains(&arg_order.base10_digits()) {
+            errors.push(Error::new_spanned(arg_order, format!("duplicate `arg_order = {}`", arg_order.base10_digits())));
+        } else {
+            seen.push(arg_order.base10_digits());
+        }
+    }
+}
+
+/// Spellings under which `Option` is recognized without `#[builder(optional)]`.
+const OPTION_PATHS: &[&[&str]] = &[
+    &["Option"],
+    &["std", "option", "Option"],
+    &["core", "option", "Option"],
+];
+
+/// Spellings under which `Vec` is accepted for `#[builder(each = "...")]`.
+const VEC_PATHS: &[&[&str]] = &[
+    &["Vec"],
+    &["std", "vec", "Vec"],
+    &["alloc", "vec", "Vec"],
+];
+
+const MAP_PATHS: &[&[&str]] = &[
+    &["HashMap"],
+    &["std", "collections", "HashMap"],
+    &["std", "collections", "hash_map", "HashMap"],
+];
+
+/// Spellings under which `PhantomData` and `PhantomPinned` are filled in
+/// without a setter. Like derive_debug's `is_phantom_data` the path has to
+/// match exactly, so a user type that happens to be named `PhantomData` still
+/// gets one.
+const MARKER_PATHS: &[&[&str]] = &[
+    &["PhantomData"],
+    &["marker", "PhantomData"],
+    &["std", "marker", "PhantomData"],
+    &["core", "marker", "PhantomData"],
+    &["PhantomPinned"],
+    &["marker", "PhantomPinned"],
+    &["std", "marker", "PhantomPinned"],
+    &["core", "marker", "PhantomPinned"],
+];
+
+/// Pointer types understood by `#[builder(setter(wrap))]`.
+const BOX_PATHS: &[&[&str]] = &[
+    &["Box"],
+    &["std", "boxed", "Box"],
+    &["alloc", "boxed", "Box"],
+];
+const RC_PATHS: &[&[&str]] = &[
+    &["Rc"],
+    &["std", "rc", "Rc"],
+    &["alloc", "rc", "Rc"],
+];
+const ARC_PATHS: &[&[&str]] = &[
+    &["Arc"],
+    &["std", "sync", "Arc"],
+    &["alloc", "sync", "Arc"],
+];
+const COW_PATHS: &[&[&str]] = &[
+    &["Cow"],
+    &["std", "borrow", "Cow"],
+    &["alloc", "borrow", "Cow"],
+];
+
+fn get_inner_type(ty: &Type) -> Option<Vec<&Type>> {
+    if let Type::Path(
+        TypePath {
+            path: Path {
+                ref segments,
+                ..
+            },
+            ..
+        }
+    ) = ty {
+
+        if let Some(seg) = segments.last() {
+            if let PathArguments::AngleBracketed(
+                syn::AngleBracketedGenericArguments {
+                    ref args,
+                    ..
+                }
+            ) = seg.arguments {
+                let generic_types: Vec<_> = args.iter()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None
+                    }).collect();
+
+                if !generic_types.is_empty() {
+                    return Some(generic_types);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the single type argument of `ty` if it is spelled exactly as one of
+/// `paths` (optionally with a leading `::`), e.g. `std::option::Option<T>`.
+fn get_std_type_argument<'a>(ty: &'a Type, paths: &[&[&str]]) -> Option<&'a Type> {
+    match get_std_type_arguments(ty, paths) {
+        Some(inner_types) if inner_types.len() == 1 => Some(inner_types[0]),
+        _ => None,
+    }
+}
+
+/// Like `get_std_type_argument`, for types with several type arguments.
+fn get_std_type_arguments<'a>(ty: &'a Type, paths: &[&[&str]]) -> Option<Vec<&'a Type>> {
+    if !is_std_path(ty, paths) {
+        return None;
+    }
+
+    get_inner_type(ty)
+}
+
+/// Whether `ty` is spelled exactly as one of `paths`, with type arguments
+/// allowed only on the last segment.
+fn is_std_path(ty: &Type, paths: &[&[&str]]) -> bool {
+    let path = match ty {
+        Type::Path(TypePath { qself: None, path }) => path,
+        _ => return false,
+    };
+
+    paths.iter().any(|expected| {
+        path.segments.len() == expected.len()
+            && (path.leading_colon.is_none() || expected.len() > 1)
+            && path.segments.iter().zip(expected.iter()).enumerate().all(|(i, (seg, name))| {
+                seg.ident == name && (i + 1 == expected.len() || seg.arguments.is_none())
+            })
+    })
+}
+
+fn get_collection(ty: &Type) -> Option<Collection<'_>> {
+    if let Some(elem_ty) = get_std_type_argument(ty, VEC_PATHS) {
+        return Some(Collection::Vec { ty, elem_ty });
+    }
+    match get_std_type_arguments(ty, MAP_PATHS)?.as_slice() {
+        // 第三个类型参数是 hasher
+        [key_ty, value_ty] | [key_ty, value_ty, _] => Some(Collection::Map { ty, key_ty, value_ty }),
+        _ => None,
+    }
+}
+
+/// Zero-sized marker types with a single value: `()` and the two types of
+/// `MARKER_PATHS`.
+fn is_marker(ty: &Type) -> bool {
+    match ty {
+        Type::Tuple(tuple) => tuple.elems.is_empty(),
+        _ => is_std_path(ty, MARKER_PATHS),
+    }
+}
+
+fn get_pointer(ty: &Type) -> Option<Pointer<'_>> {
+    if get_std_type_argument(ty, COW_PATHS).is_some() {
+        return Some(Pointer::Cow(ty));
+    }
+    get_std_type_argument(ty, BOX_PATHS).map(Pointer::Box)
+        .or_else(|| get_std_type_argument(ty, RC_PATHS).map(Pointer::Rc))
+        .or_else(|| get_std_type_argument(ty, ARC_PATHS).map(Pointer::Arc))
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    each: Option<Ident>,
+    optional: Option<Path>,
+    required: Option<Path>,
+    wrap: Option<Path>,
+    arg_order: Option<LitInt>,
+    default_empty: Option<Path>,
+    default_with: Option<Path>,
+    lazy_default: Option<Path>,
+}
+
+fn parse_field_attrs(field: &Field, errors: &mut Errors) -> FieldAttrs {
+    let mut ret = FieldAttrs::default();
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("each") {
+                let lit: LitStr = meta.value()?.parse()?;   // 注意这里需要先解析为 LitStr，即字面常量
+                ret.each = Some(lit.parse()?);              // 再将 LitStr 解析为 Ident，不能跳过 LitStr 直接解析为 Ident
+                Ok(())
+            } else if meta.path.is_ident("default_with") {
+                let lit: LitStr = meta.value()?.parse()?;
+                ret.default_with = Some(lit.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("lazy_default") {
+                ret.lazy_default = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("default_empty") {
+                ret.default_empty = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("optional") {
+                ret.optional = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("required") {
+                ret.required = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("arg_order") {
+                let lit: LitInt = meta.value()?.parse()?;
+                lit.base10_parse::<usize>()?;
+                ret.arg_order = Some(lit);
+                Ok(())
+            } else if meta.path.is_ident("setter") {
+                meta.parse_nested_meta(|setter| {
+                    if setter.path.is_ident("wrap") {
+                        ret.wrap = Some(setter.path);
+                    } else {
+                        errors.push(unknown_key_error(&setter.path, "setter option", &["wrap"]));
+                        skip_meta_value(&setter)?;
+                    }
+                    Ok(())
+                })
+            } else {
+                errors.push(unknown_key_error(&meta.path, "builder attribute", FIELD_KEYS));
+                skip_meta_value(&meta)
+            }
+        });
+        if let Err(err) = parsed {
+            errors.push(err);
+        }
+    }
+
+    ret
+}
+
+fn parse_container_options<'a>(input: &'a DeriveInput, errors: &mut Errors) -> ContainerOptions<'a> {
+    let mut ret = ContainerOptions {
+        vis: &input.vis,
+        ident: &input.ident,
+        generics: &input.generics,
+        pattern: Pattern::Clone,
+        constructor_args: false,
+        no_std: false,
+        serde: false,
+        args: false,
+        doc: doc_comment(&input.attrs),
+    };
+    let mut constructor_args = None::<Path>;
+    let mut args = None::<Path>;
+
+    for attr in &input.attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("pattern") {
+                let lit: LitStr = meta.value()?.parse()?;
+                match lit.value().as_str() {
+                    "clone" => ret.pattern = Pattern::Clone,
+                    "take" => ret.pattern = Pattern::Take,
+                    _ => errors.push(Error::new_spanned(lit, r#"expected `pattern = "clone"` or `pattern = "take"`"#)),
+                }
+            } else if meta.path.is_ident("constructor_args") {
+                ret.constructor_args = true;
+                constructor_args = Some(meta.path);
+            } else if meta.path.is_ident("no_std") {
+                ret.no_std = true;
+            } else if meta.path.is_ident("args") {
+                ret.args = true;
+                args = Some(meta.path);
+            } else if meta.path.is_ident("serde") {
+                if cfg!(feature = "serde") {
+                    ret.serde = true;
+                } else {
+                    errors.push(meta.error("`builder(serde)` requires the `serde` feature of derive_builder"));
+                }
+            } else {
+                errors.push(unknown_key_error(&meta.path, "builder attribute", CONTAINER_KEYS));
+                skip_meta_value(&meta)?;
+            }
+            Ok(())
+        });
+        if let Err(err) = parsed {
+            errors.push(err);
+        }
+    }
+
+    // builder_with 返回的 builder 直接保存必要字段的值，take 之后无法再次 build
+    if let (Some(constructor_args), Pattern::Take) = (constructor_args, ret.pattern) {
+        errors.push(Error::new_spanned(constructor_args, r#"`constructor_args` cannot be combined with `pattern = "take"`"#));
+    }
+    if let (Some(args), true) = (args, ret.no_std) {
+        errors.push(Error::new_spanned(args, "`args` parses `std::env::args()` and cannot be combined with `no_std`"));
+    }
+
+    ret
+}
+
+const FIELD_KEYS: &[&str] = &[
+    "each", "default_empty", "optional", "required", "default_with", "lazy_default", "arg_order", "setter",
+];
+const CONTAINER_KEYS: &[&str] = &["pattern", "constructor_args", "no_std", "serde", "args"];
+
+/// Joins the lines of `///` doc comments, dropping the space after `///`.
+fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    let lines: Vec<_> = attrs.iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect();
+    lines.join("\n").trim().to_owned()
+}
+
+/// Collects every attribute problem so that all of them are reported in one
+/// compile pass, each at its own span.
+#[derive(Default)]
+struct Errors(Option<Error>);
+
+impl Errors {
+    fn push(&mut self, error: Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.0 {
+            Some(errors) => Err(errors),
+            None => Ok(()),
+        }
+    }
+}
+
+fn unknown_key_error(path: &Path, what: &str, supported: &[&str]) -> Error {
+    let key = quote!(#path).to_string().replace(' ', "");
+    let closest = supported.iter()
+        .map(|candidate| (edit_distance(&key, candidate), candidate))
+        .min()
+        .filter(|(distance, candidate)| *distance <= std::cmp::max(2, candidate.len() / 3));
+    let message = match closest {
+        Some((_, candidate)) => format!("unknown {} `{}`, did you mean `{}`?", what, key, candidate),
+        None => {
+            let expected: Vec<_> = supported.iter().map(|key| format!("`{}`", key)).collect();
+            format!("unknown {} `{}`, expected one of {}", what, key, expected.join(", "))
+        }
+    };
+    Error::new_spanned(path, message)
+}
+
+/// Skips `= value` or `(...)` after an unknown key so that parsing can go on
+/// with the keys after it.
+fn skip_meta_value(meta: &syn::meta::ParseNestedMeta) -> Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let _content;
+        syn::parenthesized!(_content in meta.input);
+    }
+    Ok(())
+}
+
+/// Levenshtein distance, used to suggest the supported key closest to a typo.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            cur.push(substitution.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
@@ -0,0 +1,55 @@
+// Only `Option`, `std::option::Option` and `core::option::Option` are treated
+// as optional fields automatically. A type that merely happens to be called
+// `Option`, or an alias of the real one, is opted in or out explicitly with
+// `#[builder(optional)]` and `#[builder(required)]`.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+mod custom {
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Option<T>(pub T);
+}
+
+type MaybePath = Option<PathBuf>;
+
+#[derive(Builder)]
+pub struct Command {
+    /// Not `std::option::Option`, so it stays required.
+    executable: custom::Option<String>,
+    current_dir: core::option::Option<String>,
+    timeout: ::std::option::Option<u64>,
+    #[builder(optional)]
+    log_file: MaybePath,
+    #[builder(required)]
+    user: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable(custom::Option("cargo".to_owned()))
+        .user(None)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, custom::Option("cargo".to_owned()));
+    assert!(command.current_dir.is_none());
+    assert!(command.timeout.is_none());
+    assert!(command.log_file.is_none());
+    assert!(command.user.is_none());
+
+    let command = Command::builder()
+        .executable(custom::Option("cargo".to_owned()))
+        .current_dir("..".to_owned())
+        .timeout(30)
+        .log_file(PathBuf::from("build.log"))
+        .user(Some("root".to_owned()))
+        .build()
+        .unwrap();
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, Some(30));
+    assert_eq!(command.log_file, Some(PathBuf::from("build.log")));
+    assert_eq!(command.user.as_deref(), Some("root"));
+
+    assert!(Command::builder().user(None).build().is_err());
+    assert!(Command::builder().executable(custom::Option(String::new())).build().is_err());
+}
@@ -0,0 +1,54 @@
+// Field attributes that cannot apply to the field's type are reported at the
+// offending tokens instead of panicking inside the macro.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Other {
+    #[builder(optional, required)]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Wrapped {
+    #[builder(setter(wrap))]
+    name: String,
+}
+
+#[derive(Builder)]
+#[builder(constructor_args)]
+pub struct Ordered {
+    #[builder(arg_order = 1)]
+    executable: String,
+    #[builder(arg_order = 1)]
+    shell: bool,
+}
+
+#[derive(Builder)]
+pub struct Defaulted {
+    #[builder(each = "arg", default_empty)]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Lazy {
+    #[builder(lazy_default)]
+    port: u16,
+    #[builder(default_with = "free_port", optional)]
+    fallback: Option<u16>,
+}
+
+#[derive(Builder)]
+pub struct NotOption {
+    #[builder(optional)]
+    retries: u32,
+}
+
+fn main() {}
@@ -0,0 +1,75 @@
+// With `#[builder(setter(wrap))]` the setter of a `Box`, `Rc`, `Arc` or `Cow`
+// field accepts the value to be wrapped and does the wrapping itself, so call
+// sites do not have to write `Box::new(...)` by hand.
+//
+// `build()` clones every field, so the pointers used here must be Clone.
+
+use derive_builder::Builder;
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub trait Handler {
+    fn handle(&self) -> String;
+}
+
+struct Echo(&'static str);
+
+impl Handler for Echo {
+    fn handle(&self) -> String {
+        self.0.to_owned()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    verbose: bool,
+}
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(wrap))]
+    handler: Rc<dyn Handler>,
+    #[builder(setter(wrap))]
+    config: Arc<Config>,
+    #[builder(setter(wrap))]
+    defaults: Box<Config>,
+    #[builder(setter(wrap))]
+    name: Cow<'static, str>,
+    #[builder(setter(wrap))]
+    description: Option<std::boxed::Box<str>>,
+    #[builder(each = "hook", setter(wrap))]
+    hooks: Vec<Arc<dyn Handler + Send + Sync>>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .handler(Echo("handled"))
+        .config(Config { verbose: true })
+        .defaults(Config { verbose: false })
+        .name("cargo")
+        .description("build tool")
+        .hook(Echo("first"))
+        .hook(Echo("second"))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.handler.handle(), "handled");
+    assert_eq!(*command.config, Config { verbose: true });
+    assert_eq!(*command.defaults, Config { verbose: false });
+    assert_eq!(command.name, "cargo");
+    assert_eq!(command.description.as_deref(), Some("build tool"));
+    let hooks: Vec<_> = command.hooks.iter().map(|hook| hook.handle()).collect();
+    assert_eq!(hooks, ["first", "second"]);
+
+    let command = Command::builder()
+        .handler(Echo("handled"))
+        .config(Config { verbose: false })
+        .defaults(Config { verbose: false })
+        .name(String::from("rustc"))
+        .build()
+        .unwrap();
+    assert_eq!(command.name, "rustc");
+    assert!(command.description.is_none());
+    assert!(command.hooks.is_empty());
+}
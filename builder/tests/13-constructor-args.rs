@@ -0,0 +1,45 @@
+// `#[builder(constructor_args)]` adds `builder_with`, which takes every
+// required field positionally. Required fields are ordered by declaration
+// unless `#[builder(arg_order = N)]` says otherwise. Only the optional and
+// repeated fields are left as setters, so the returned builder's `build()`
+// has nothing left to check and returns the struct directly.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(constructor_args)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(arg_order = 0)]
+    shell: bool,
+    timeout: u64,
+}
+
+fn main() {
+    let command: Command = Command::builder_with(true, "bash".to_owned(), 30)
+        .arg("-c".to_owned())
+        .arg("whoami".to_owned())
+        .build();
+    assert!(command.shell);
+    assert_eq!(command.executable, "bash");
+    assert_eq!(command.timeout, 30);
+    assert_eq!(command.args, ["-c", "whoami"]);
+    assert!(command.current_dir.is_none());
+
+    let command = Command::builder_with(false, "ls".to_owned(), 5)
+        .current_dir("/".to_owned())
+        .build();
+    assert_eq!(command.current_dir.as_deref(), Some("/"));
+
+    // The regular builder is still available.
+    let command = Command::builder()
+        .executable("ls".to_owned())
+        .shell(false)
+        .timeout(5)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "ls");
+}
@@ -0,0 +1,42 @@
+// `build_with` configures a fresh builder inside a closure and builds it in one
+// expression. `try_build_from_fn` does the same for closures that can fail;
+// both report errors with the same type as `build()`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    timeout: Option<u64>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let command = Command::build_with(|b| {
+        b.executable("ls".to_owned()).arg("-l".to_owned());
+    })?;
+    assert_eq!(command.executable, "ls");
+    assert_eq!(command.args, ["-l"]);
+
+    assert!(Command::build_with(|b| {
+        b.arg("-l".to_owned());
+    })
+    .is_err());
+
+    let command = Command::try_build_from_fn(|b| {
+        b.executable("sleep".to_owned()).timeout("30".parse()?);
+        Ok(())
+    })?;
+    assert_eq!(command.timeout, Some(30));
+
+    let err = Command::try_build_from_fn(|b| {
+        b.executable("sleep".to_owned()).timeout("soon".parse()?);
+        Ok(())
+    })
+    .err()
+    .unwrap();
+    assert_eq!(err.to_string(), "invalid digit found in string");
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// With `#[builder(no_std)]` the expansion only refers to `::core` and
+// `::alloc`, and `build()` fails with a generated `CommandBuilderError` that
+// implements `core::fmt::Display` without allocating.
+//
+// The crate below is `no_std`, so any leftover `std::...` path in the
+// expansion fails to resolve. std is linked from a private module only to
+// provide the runtime for the test binary.
+
+#![no_std]
+
+extern crate alloc;
+
+mod host {
+    extern crate std;
+    pub use std::string::ToString;
+}
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(no_std)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(setter(wrap))]
+    label: Option<Cow<'static, str>>,
+    #[builder(setter(wrap))]
+    limits: Box<[u32]>,
+}
+
+mod redefined {
+    use super::Builder;
+
+    type Option = ();
+    type Some = ();
+    type None = ();
+    type Result = ();
+    type Box = ();
+
+    #[derive(Builder)]
+    #[builder(no_std)]
+    pub struct Command {
+        executable: alloc::string::String,
+    }
+}
+
+fn no_shell() -> core::result::Result<String, ()> {
+    core::result::Result::Err(())
+}
+
+#[derive(Builder)]
+#[builder(no_std)]
+pub struct Shell {
+    #[builder(default_with = "no_shell")]
+    shell: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".into())
+        .arg("build".into())
+        .label("release")
+        .limits(&[1, 2, 3])
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build"]);
+    assert_eq!(command.label.as_deref(), core::option::Option::Some("release"));
+    assert_eq!(*command.limits, [1, 2, 3]);
+
+    let err = Command::builder().limits(&[]).build().err().unwrap();
+    assert_eq!(err, CommandBuilderError::MissingField("executable"));
+    assert_eq!(host::ToString::to_string(&err), "executable field missing");
+
+    let err = redefined::Command::build_with(|_| {}).err().unwrap();
+    assert_eq!(err, redefined::CommandBuilderError::MissingField("executable"));
+
+    let err = Shell::builder().build().err().unwrap();
+    assert_eq!(err, ShellBuilderError::DefaultFailed("shell"));
+    assert_eq!(host::ToString::to_string(&err), "failed to compute default for shell");
+}
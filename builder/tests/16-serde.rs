@@ -0,0 +1,41 @@
+// With the `serde` feature, `#[builder(serde)]` makes the builder itself
+// Deserialize and Serialize so that partial configuration documents can be
+// loaded into it. Missing keys leave fields unset, `each` fields read arrays,
+// and `build()` reports every required key that never showed up.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(serde)]
+pub struct Command {
+    executable: String,
+    shell: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut builder: CommandBuilder = serde_json::from_str(r#"{"args": ["build", "--release"]}"#).unwrap();
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "executable, shell fields missing");
+
+    builder.shell("sh".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "executable field missing");
+
+    let command = builder.executable("cargo".to_owned()).arg("-v".to_owned()).build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build", "--release", "-v"]);
+    assert!(command.current_dir.is_none());
+
+    let builder: CommandBuilder = serde_json::from_str(r#"{"executable": "ls", "shell": "sh", "current_dir": "/"}"#).unwrap();
+    let command = builder.build().unwrap();
+    assert_eq!(command.current_dir.as_deref(), Some("/"));
+    assert!(command.args.is_empty());
+
+    // Unset fields are left out when writing a partial document back.
+    let mut builder = Command::builder();
+    builder.arg("--help".to_owned());
+    assert_eq!(serde_json::to_string(&builder).unwrap(), r#"{"args":["--help"]}"#);
+}
@@ -0,0 +1,20 @@
+// Every malformed builder attribute is reported in the same compile pass, each
+// at its own span. Unknown keys suggest the closest supported key.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(no_sdt)]
+pub struct Command {
+    executable: String,
+    #[builder(eac = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env", requierd)]
+    env: Vec<String>,
+    #[builder(setter(warp), bogus)]
+    current_dir: Option<String>,
+    #[builder(each = "bad")]
+    timeout: u64,
+}
+
+fn main() {}
@@ -0,0 +1,117 @@
+// `#[builder(pattern = "take")]` switches `build()` to `&mut self` and moves
+// the values out of the builder instead of cloning them, so fields don't need
+// to implement Clone. All required fields are checked before anything is
+// taken, so a failed `build()` leaves the builder untouched.
+//
+// The builder and its methods also take the visibility of the input struct.
+//
+// Repeated and optional fields, collections, markers and default providers are
+// moved out the same way: after a successful build the builder is empty again.
+
+mod jobs {
+    use derive_builder::Builder;
+
+    pub trait Handler {
+        fn handle(&self) -> String;
+    }
+
+    #[derive(Builder)]
+    #[builder(pattern = "take")]
+    pub(crate) struct Job {
+        pub name: String,
+        #[builder(setter(wrap))]
+        pub handler: Box<dyn Handler>,
+        #[builder(each = "step", setter(wrap))]
+        pub steps: Vec<Box<dyn Handler>>,
+        pub retries: Option<u32>,
+    }
+}
+
+use derive_builder::Builder;
+use jobs::{Handler, Job};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+thread_local! {
+    static LOOKUPS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn free_port() -> Result<u16, std::num::ParseIntError> {
+    LOOKUPS.with(|lookups| lookups.set(lookups.get() + 1));
+    "8080".parse()
+}
+
+pub struct Release;
+
+#[derive(Builder)]
+#[builder(pattern = "take")]
+pub struct Command<Profile> {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+    #[builder(each = "tag", default_empty)]
+    tags: Option<Vec<String>>,
+    current_dir: Option<String>,
+    #[builder(default_with = "free_port")]
+    port: u16,
+    profile: PhantomData<Profile>,
+}
+
+struct Echo(&'static str);
+
+impl Handler for Echo {
+    fn handle(&self) -> String {
+        self.0.to_owned()
+    }
+}
+
+fn main() {
+    let mut builder = Job::builder();
+    builder.handler(Echo("run")).step(Echo("a")).step(Echo("b")).retries(3);
+    assert!(builder.build().is_err());
+
+    let job = builder.name("deploy".to_owned()).build().unwrap();
+    assert_eq!(job.name, "deploy");
+    assert_eq!(job.handler.handle(), "run");
+    let steps: Vec<_> = job.steps.iter().map(|step| step.handle()).collect();
+    assert_eq!(steps, ["a", "b"]);
+    assert_eq!(job.retries, Some(3));
+
+    // Everything was moved out by the previous build.
+    assert!(builder.build().is_err());
+
+    let job = Job::build_with(|b| {
+        b.name("noop".to_owned()).handler(Echo("noop"));
+    })
+    .unwrap();
+    assert!(job.steps.is_empty());
+    assert!(job.retries.is_none());
+
+    let mut builder = Command::<Release>::builder();
+    builder
+        .arg("build".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
+        .tag("ci".to_owned())
+        .current_dir("..".to_owned());
+    assert!(builder.build().is_err());
+    assert_eq!(LOOKUPS.with(Cell::get), 0);
+
+    let command = builder.executable("cargo".to_owned()).build().unwrap();
+    assert_eq!(command.args, ["build"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.tags, Some(vec!["ci".to_owned()]));
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.port, 8080);
+    let PhantomData = command.profile;
+
+    let command = builder.executable("rustc".to_owned()).port(80).build().unwrap();
+    assert!(command.args.is_empty());
+    assert!(command.env.is_empty());
+    assert_eq!(command.tags, Some(vec![]));
+    assert!(command.current_dir.is_none());
+    assert_eq!(command.port, 80);
+    assert_eq!(LOOKUPS.with(Cell::get), 1);
+}
@@ -0,0 +1,41 @@
+// Inputs the builder cannot handle are rejected with an error at the relevant
+// tokens rather than a panic inside the macro.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Meters(f64);
+
+#[derive(Builder)]
+pub enum Shape {
+    Circle { radius: f64 },
+}
+
+#[derive(Builder)]
+#[builder(pattern = "move")]
+pub struct Command {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(constructor_args, pattern = "take")]
+pub struct Positional {
+    executable: String,
+}
+
+// `from_args` handles `--help` and `help()` lives next to the setters.
+#[derive(Builder)]
+#[builder(args)]
+pub struct Cli {
+    help: bool,
+    name: String,
+}
+
+#[derive(Builder)]
+#[builder(args)]
+pub struct Docs {
+    #[builder(each = "help")]
+    topics: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+// `each` also works on `HashMap<K, V>` and on an `Option` of either
+// collection. The first `each` call turns a `None` into `Some`; an untouched
+// optional collection builds to `None`, unless `default_empty` asks for an
+// empty collection instead.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Option<Vec<String>>,
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+    #[builder(each = "label")]
+    labels: Option<std::collections::HashMap<String, u32>>,
+    #[builder(each = "tag", default_empty)]
+    tags: Option<Vec<String>>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
+        .label("priority".to_owned(), 1)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, Some(vec!["build".to_owned(), "--release".to_owned()]));
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.labels.unwrap()["priority"], 1);
+    assert_eq!(command.tags, Some(vec![]));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .tags(vec!["ci".to_owned()])
+        .build()
+        .unwrap();
+
+    assert!(command.args.is_none());
+    assert!(command.env.is_empty());
+    assert!(command.labels.is_none());
+    assert_eq!(command.tags, Some(vec!["ci".to_owned()]));
+}
@@ -0,0 +1,53 @@
+// `PhantomData`, `PhantomPinned` and unit fields have exactly one value, so the
+// builder fills them in itself and generates no setter for them. Generic
+// parameters that only show up inside `PhantomData` are carried over to the
+// builder.
+//
+// Only the real marker types count: a type of your own that happens to be
+// named `PhantomData` is an ordinary field with a setter.
+
+use derive_builder::Builder;
+use std::marker::{PhantomData, PhantomPinned};
+
+pub struct Meters;
+
+#[derive(Builder)]
+pub struct Distance<Unit> {
+    value: f64,
+    unit: PhantomData<Unit>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "take")]
+pub struct Pinned<'a, T: ?Sized> {
+    name: &'a str,
+    data: std::marker::PhantomData<fn() -> Box<T>>,
+    _pin: PhantomPinned,
+    unit: (),
+}
+
+mod mylib {
+    #[derive(Clone)]
+    pub struct PhantomData<T>(pub T);
+}
+
+#[derive(Builder)]
+pub struct Tagged {
+    tag: mylib::PhantomData<u32>,
+    marker: core::marker::PhantomData<u32>,
+}
+
+fn main() {
+    let distance: Distance<Meters> = Distance::builder().value(1.5).build().unwrap();
+    assert_eq!(distance.value, 1.5);
+    let PhantomData = distance.unit;
+
+    let pinned = Pinned::<str>::builder().name("pinned").build().unwrap();
+    assert_eq!(pinned.name, "pinned");
+    let () = pinned.unit;
+
+    assert!(Tagged::builder().build().is_err());
+    let tagged = Tagged::builder().tag(mylib::PhantomData(7)).build().unwrap();
+    assert_eq!(tagged.tag.0, 7);
+    let PhantomData = tagged.marker;
+}
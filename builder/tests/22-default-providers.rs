@@ -0,0 +1,86 @@
+// `#[builder(default_with = "path")]` fills an unset field from a function
+// returning `Result<T, E>`. The function only runs when the field was not set,
+// and its error comes out of `build()`.
+//
+// With `lazy_default` the provider is an `async fn` instead; it is awaited by
+// a generated `build_async()`, while plain `build()` reports the unset field
+// as missing.
+
+use derive_builder::Builder;
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+thread_local! {
+    static LOOKUPS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn free_port() -> Result<u16, std::num::ParseIntError> {
+    LOOKUPS.with(|lookups| lookups.set(lookups.get() + 1));
+    "8080".parse()
+}
+
+fn read_config() -> Result<String, std::io::Error> {
+    std::fs::read_to_string("/nonexistent/server.toml")
+}
+
+async fn resolve_host() -> Result<String, String> {
+    Ok("localhost".to_owned())
+}
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(default_with = "free_port")]
+    port: u16,
+    #[builder(default_with = "resolve_host", lazy_default)]
+    host: String,
+}
+
+#[derive(Builder)]
+pub struct Configured {
+    #[builder(default_with = "read_config")]
+    config: String,
+}
+
+#[derive(Builder)]
+#[builder(constructor_args)]
+pub struct Listener {
+    name: String,
+    #[builder(default_with = "free_port")]
+    port: u16,
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn main() {
+    let server = Server::builder().port(80).host("example.com".to_owned()).build().unwrap();
+    assert_eq!(server.port, 80);
+    assert_eq!(server.host, "example.com");
+    assert_eq!(LOOKUPS.with(Cell::get), 0);
+
+    let server = block_on(Server::builder().build_async()).unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.host, "localhost");
+    assert_eq!(LOOKUPS.with(Cell::get), 1);
+
+    let err = Server::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "host field missing");
+
+    let err = Configured::builder().build().err().unwrap();
+    assert!(err.downcast_ref::<std::io::Error>().is_some());
+
+    let listener = Listener::builder_with("api".to_owned()).build().unwrap();
+    assert_eq!((listener.name.as_str(), listener.port), ("api", 8080));
+
+    let configured = Configured::builder().config(String::new()).build().unwrap();
+    assert!(configured.config.is_empty());
+}
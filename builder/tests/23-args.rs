@@ -0,0 +1,92 @@
+// `#[builder(args)]` generates `CommandBuilder::from_args`, which fills the
+// builder from command line arguments:
+//
+//     --executable cargo --arg build --arg=--release --env RUST_LOG=debug --verbose
+//
+// Values are parsed with `FromStr`, `each` fields take one value per repeated
+// flag and bool fields are plain flags. `CommandBuilder::help()` lists the
+// options with the fields' doc comments; `--help` fails with that text.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+/// Runs a program.
+#[derive(Builder)]
+#[builder(args)]
+pub struct Command {
+    /// Program to run
+    executable: String,
+    /// Argument passed to the program
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    /// Environment variable as KEY=VALUE
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+    /// Working directory
+    current_dir: Option<String>,
+    /// Seconds before the program is killed
+    timeout_secs: Option<u64>,
+    /// Print the command before running it
+    verbose: bool,
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn main() {
+    let command = CommandBuilder::from_args(args(&[
+        "--executable", "cargo",
+        "--arg", "build",
+        "--arg=--release",
+        "--env", "RUST_LOG=debug",
+        "--timeout-secs=30",
+        "--verbose",
+    ]))
+    .unwrap()
+    .build()
+    .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout_secs, Some(30));
+    assert!(command.verbose);
+
+    let command = CommandBuilder::from_args(args(&["--executable=ls"])).unwrap().build().unwrap();
+    assert!(!command.verbose);
+
+    let err = CommandBuilder::from_args(args(&["--timeout-secs", "soon"])).err().unwrap();
+    assert_eq!(err.to_string(), "invalid value `soon` for `--timeout-secs`: invalid digit found in string");
+
+    let err = CommandBuilder::from_args(args(&["--executable"])).err().unwrap();
+    assert_eq!(err.to_string(), "`--executable` expects a value");
+
+    let err = CommandBuilder::from_args(args(&["--env", "RUST_LOG"])).err().unwrap();
+    assert_eq!(err.to_string(), "`--env` expects KEY=VALUE, got `RUST_LOG`");
+
+    let err = CommandBuilder::from_args(args(&["build"])).err().unwrap();
+    assert_eq!(err.to_string(), "unexpected argument `build`");
+
+    let err = CommandBuilder::from_args(args(&[])).unwrap().build().err().unwrap();
+    assert_eq!(err.to_string(), "executable field missing");
+
+    let help = "\
+Usage: command [OPTIONS]
+
+Runs a program.
+
+Options:
+  --executable <EXECUTABLE>      Program to run (required)
+  --arg <ARG>...                 Argument passed to the program
+  --env <KEY=VALUE>...           Environment variable as KEY=VALUE
+  --current-dir <CURRENT_DIR>    Working directory
+  --timeout-secs <TIMEOUT_SECS>  Seconds before the program is killed
+  --verbose                      Print the command before running it
+  --help                         Print this help
+";
+    assert_eq!(CommandBuilder::help(), help);
+    let err = CommandBuilder::from_args(args(&["--help"])).err().unwrap();
+    assert_eq!(err.to_string(), help);
+}
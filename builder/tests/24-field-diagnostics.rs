@@ -0,0 +1,46 @@
+// Type errors caused by a field land on that field rather than on the
+// `#[derive(Builder)]` line:
+//
+//   - with the default clone pattern every field must be `Clone`
+//   - a `default_with` provider must return the field's type
+//   - with `#[builder(args)]` every value must implement `FromStr`
+//   - map keys of `each` fields must be `Eq + Hash`
+
+use derive_builder::Builder;
+
+pub struct Handle;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    handle: Handle,
+    #[builder(each = "arg")]
+    args: Vec<Handle>,
+}
+
+fn default_port() -> Result<String, std::io::Error> {
+    Ok(String::new())
+}
+
+#[derive(Builder)]
+#[builder(pattern = "take")]
+pub struct Server {
+    #[builder(default_with = "default_port")]
+    port: u16,
+}
+
+#[derive(Builder)]
+#[builder(args, pattern = "take")]
+pub struct Cli {
+    #[builder(each = "handle")]
+    handles: Vec<Handle>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "take")]
+pub struct Registry {
+    #[builder(each = "entry")]
+    entries: std::collections::HashMap<Handle, String>,
+}
+
+fn main() {}
@@ -10,4 +10,20 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-option-detection.rs");
+    t.compile_fail("tests/11-invalid-field-attributes.rs");
+    t.pass("tests/12-wrapped-setters.rs");
+    t.pass("tests/13-constructor-args.rs");
+    t.pass("tests/14-build-with-closure.rs");
+    t.pass("tests/15-no-std.rs");
+    t.compile_fail("tests/17-multiple-attribute-errors.rs");
+    t.pass("tests/18-take-pattern.rs");
+    t.compile_fail("tests/19-unsupported-input.rs");
+    t.pass("tests/20-collection-shapes.rs");
+    t.pass("tests/21-marker-fields.rs");
+    t.pass("tests/22-default-providers.rs");
+    t.pass("tests/23-args.rs");
+    t.compile_fail("tests/24-field-diagnostics.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/16-serde.rs");
 }
@@ -0,0 +1,1592 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use std::collections::HashSet as Set;
+use std::fmt::Display;
+use std::ops::Range;
+use syn::{
+    ext::IdentExt,
+    meta::ParseNestedMeta,
+    parse_macro_input, parse_quote,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token,
+    visit::{self, Visit},
+    Attribute, Data, DataEnum, DeriveInput, Error, Expr, ExprLit, Field, Fields, Generics, Ident,
+    Lit, LitInt, LitStr, Member, Meta, Path, PredicateType, Result, Token, Type, TypeBareFn,
+    TypeImplTrait, TypeParamBound, TypePath, TypePtr, TypeTraitObject, Variant, WhereClause,
+    WherePredicate,
+};
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    // eprintln!("{:#?}", input);
+    let expand = expand(input).unwrap_or_else(|e| e.to_compile_error());
+    TokenStream::from(expand)
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let input_ident = input.ident;
+    let container = ContainerAttrs::parse(&input.attrs)?;
+
+    // 同时收集所有会输出的字段，用于推断泛型参数的约束
+    let (body, debug_fields) = match &input.data {
+        Data::Struct(s) => {
+            let debug_fields = DebugField::from_fields(s.fields.iter(), &container)?;
+            let body = make_fields_body(
+                &container.name(&input_ident),
+                &s.fields,
+                &debug_fields,
+                &container,
+                |field| {
+                    let member = &field.member;
+                    quote_spanned!(field.ty.span()=> self.#member)
+                },
+            );
+            (body, debug_fields)
+        }
+        Data::Enum(e) => {
+            // 枚举的名字不会出现在输出中，只能重命名变体
+            if let Some(name) = &container.name {
+                return Err(Error::new_spanned(
+                    name,
+                    r#"`debug(name = "...")` only applies to structs and unions"#,
+                ));
+            }
+            make_enum_body(e, &container)?
+        }
+        // 无法知道 union 当前是哪个字段有效，只输出名字，也就不需要任何约束
+        Data::Union(_) => {
+            let input_ident_name = container.name(&input_ident);
+            let body = quote! {
+                f.debug_struct(#input_ident_name).finish_non_exhaustive()
+            };
+            (body, Vec::new())
+        }
+    };
+
+    let mut generics = input.generics;
+    generics.make_where_clause();
+    let mut where_clause = generics.where_clause.take().unwrap();
+    match container.bound {
+        Some(custom_clauses) => {
+            where_clause.predicates.extend(custom_clauses);
+        }
+        None => {
+            let printed_types = debug_fields
+                .iter()
+                .filter(|field| field.uses_debug())
+                .map(|field| &field.ty);
+            let used_type_params = GenericVisitor::get_used_type_params(&generics, printed_types);
+            for ty_param in used_type_params {
+                if !has_debug_bound(&generics, &where_clause, ty_param) {
+                    where_clause
+                        .predicates
+                        .push(parse_quote!(#ty_param: std::fmt::Debug));
+                }
+            }
+
+            // 展开和预设格式的字段要求整个字段类型实现对应的 trait，具体类型不需要额外的约束
+            for field in &debug_fields {
+                let bound = match field.field_bound() {
+                    Some(bound) => bound,
+                    None => continue,
+                };
+                let ty = &field.ty;
+                if !GenericVisitor::get_used_type_params(&generics, Some(ty)).is_empty() {
+                    where_clause.predicates.push(parse_quote!(#ty: #bound));
+                }
+            }
+        }
+    }
+
+    // 字段上的 bound 总是生效，它替换的只是这个字段推断出的约束
+    for field in &debug_fields {
+        if let Some(bound) = &field.bound {
+            where_clause.predicates.extend(bound.iter().cloned());
+        }
+    }
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let wrappers = make_wrappers(&debug_fields);
+
+    // 有名字段的结构体还可以被其他类型展开
+    let debug_fields_impl = match &input.data {
+        Data::Struct(s) if matches!(s.fields, Fields::Named(_)) => {
+            let builder = Ident::new("builder", Span::mixed_site());
+            let field_calls = make_field_calls(&debug_fields, &builder, None, |field| {
+                let member = &field.member;
+                quote_spanned!(field.ty.span()=> self.#member)
+            });
+            let builder = if field_calls.is_empty() {
+                quote!(_)
+            } else {
+                quote!(#builder)
+            };
+            quote! {
+                impl #impl_generics ::derive_debug::DebugFields for #input_ident #ty_generics #where_clause {
+                    fn debug_fields(&self, #builder: &mut std::fmt::DebugStruct<'_, '_>) {
+                        #wrappers
+                        #field_calls
+                    }
+                }
+            }
+        }
+        _ => TokenStream2::new(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics std::fmt::Debug for #input_ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                #wrappers
+                #body
+            }
+        }
+
+        #debug_fields_impl
+    })
+}
+
+// 按字段的形状输出，结构体和枚举的变体相同：
+//     Unit          => f.write_str("Unit")
+//     Tuple(a, b)   => f.debug_tuple("Tuple").field(a).field(b).finish()
+//     Struct { .. } => f.debug_struct("Struct").field("a", a).finish()
+// place 给出字段值的表达式，例如 self.name，或者 match 中绑定的 *__name
+fn make_fields_body(
+    name: &str,
+    fields: &Fields,
+    debug_fields: &[DebugField],
+    container: &ContainerAttrs,
+    place: impl Fn(&DebugField) -> TokenStream2,
+) -> TokenStream2 {
+    let builder = Ident::new("builder", Span::mixed_site());
+    let hidden = Ident::new("hidden", Span::mixed_site());
+
+    // non_exhaustive 时，有字段被隐藏才以 `..` 结尾
+    let always_hidden = debug_fields
+        .iter()
+        .any(|field| matches!(field.skip, Skip::Always));
+    let maybe_hidden = debug_fields
+        .iter()
+        .any(|field| matches!(field.skip, Skip::If(_)));
+    let track_hidden = container.non_exhaustive && !always_hidden && maybe_hidden;
+    let field_calls = make_field_calls(
+        debug_fields,
+        &builder,
+        track_hidden.then_some(&hidden),
+        place,
+    );
+
+    let start = match fields {
+        Fields::Named(_) => quote!(f.debug_struct(#name)),
+        Fields::Unnamed(_) => quote!(f.debug_tuple(#name)),
+        Fields::Unit => return quote!(f.write_str(#name)),
+    };
+
+    if track_hidden {
+        return quote! {{
+            let #builder = &mut #start;
+            let mut #hidden = false;
+            #field_calls
+            if #hidden {
+                #builder.finish_non_exhaustive()
+            } else {
+                #builder.finish()
+            }
+        }};
+    }
+
+    let finish = if container.non_exhaustive && always_hidden {
+        quote!(#builder.finish_non_exhaustive())
+    } else {
+        quote!(#builder.finish())
+    };
+    quote! {{
+        let #builder = &mut #start;
+        #field_calls
+        #finish
+    }}
+}
+
+fn make_enum_body(
+    data: &DataEnum,
+    container: &ContainerAttrs,
+) -> Result<(TokenStream2, Vec<DebugField>)> {
+    let mut arms = TokenStream2::new();
+    let mut all_fields = Vec::new();
+    let mut errors = None::<Error>;
+
+    for variant in &data.variants {
+        let variant_ident = &variant.ident;
+        // 继续检查其余的变体，一次报告所有的错误
+        let (variant_name, debug_fields) = match (
+            variant_name(variant, container),
+            DebugField::from_fields(variant.fields.iter(), container),
+        ) {
+            (Ok(variant_name), Ok(debug_fields)) => (variant_name, debug_fields),
+            (Err(error), Ok(_)) | (Ok(_), Err(error)) => {
+                push_error(&mut errors, error);
+                continue;
+            }
+            (Err(mut error), Err(fields_error)) => {
+                error.combine(fields_error);
+                push_error(&mut errors, error);
+                continue;
+            }
+        };
+        let members = debug_fields.iter().map(|field| &field.member);
+        // 跳过的字段被其他字段的格式字符串引用时仍然需要绑定
+        let referenced: Set<&str> = debug_fields
+            .iter()
+            .filter_map(|field| field.fmt_arg.as_ref())
+            .flat_map(|fmt_arg| fmt_arg.fields())
+            .collect();
+        let bindings = debug_fields.iter().map(|field| match field.skip {
+            Skip::Always if !referenced.contains(field.ident_name().as_str()) => quote!(_),
+            _ => {
+                let binding = &field.binding;
+                quote!(#binding)
+            }
+        });
+        let body = make_fields_body(
+            &variant_name,
+            &variant.fields,
+            &debug_fields,
+            container,
+            |field| {
+                let binding = &field.binding;
+                quote_spanned!(field.ty.span()=> *#binding)
+            },
+        );
+        // 各种形状的变体都可以用 { 0: __0, .. } 的形式匹配
+        arms.extend(quote! {
+            Self::#variant_ident { #(#members: #bindings),* } => #body,
+        });
+        all_fields.extend(debug_fields);
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    // 没有变体的枚举不可能有值，只能对 *self 做空匹配
+    if data.variants.is_empty() {
+        return Ok((quote!(match *self {}), all_fields));
+    }
+
+    let body = quote! {
+        match self {
+            #arms
+        }
+    };
+    Ok((body, all_fields))
+}
+
+// 变体上只支持 #[debug(rename = "...")]，否则按 rename_all 转换
+fn variant_name(variant: &Variant, container: &ContainerAttrs) -> Result<String> {
+    let mut rename = None::<LitStr>;
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+        let expected = r#"expected `debug(rename = "...")`"#;
+        let meta = match &attr.meta {
+            Meta::List(meta) => meta,
+            _ => return Err(Error::new_spanned(attr, expected)),
+        };
+        meta.parse_nested_meta(|nested| {
+            if !nested.path.is_ident("rename") {
+                return Err(Error::new_spanned(&nested.path, expected));
+            }
+            rename = Some(nested.value()?.parse()?);
+            Ok(())
+        })?;
+    }
+
+    Ok(match rename {
+        Some(rename) => rename.value(),
+        None => container.rename(&variant.ident),
+    })
+}
+
+/// Container attributes: `#[debug(bound = "...")]`,
+/// `#[debug(non_exhaustive)]`, `#[debug(name = "...")]` and
+/// `#[debug(rename_all = "...")]`.
+struct ContainerAttrs {
+    /// Replaces the inferred `T: Debug` bounds.
+    bound: Option<Vec<WherePredicate>>,
+    /// Ends the output with `..` whenever a field was skipped.
+    non_exhaustive: bool,
+    /// Replaces the struct or union name in the output.
+    name: Option<LitStr>,
+    /// Case conversion for every field and variant name.
+    rename_all: Option<RenameRule>,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut ret = ContainerAttrs {
+            bound: None,
+            non_exhaustive: false,
+            name: None,
+            rename_all: None,
+        };
+
+        for attr in attrs {
+            if !attr.path().is_ident("debug") {
+                continue;
+            }
+
+            let expected = r#"expected `debug(bound = "...")`, `debug(non_exhaustive)`, `debug(name = "...")` or `debug(rename_all = "...")`"#;
+            let meta = match &attr.meta {
+                Meta::List(meta) => meta,
+                _ => return Err(Error::new_spanned(attr, expected)),
+            };
+
+            meta.parse_nested_meta(|nested| {
+                if nested.path.is_ident("non_exhaustive") {
+                    ret.non_exhaustive = true;
+                    return Ok(());
+                }
+                if nested.path.is_ident("name") {
+                    ret.name = Some(nested.value()?.parse()?);
+                    return Ok(());
+                }
+                if nested.path.is_ident("rename_all") {
+                    let lit: LitStr = nested.value()?.parse()?;
+                    ret.rename_all = Some(RenameRule::parse(&lit)?);
+                    return Ok(());
+                }
+                if !nested.path.is_ident("bound") {
+                    return Err(Error::new_spanned(attr, expected));
+                }
+
+                let lit: LitStr = nested.value()?.parse()?;
+                let custom_clauses =
+                    lit.parse_with(Punctuated::<_, Token![,]>::parse_terminated)?;
+                match ret.bound.as_mut() {
+                    Some(where_clauses) => where_clauses.extend(custom_clauses),
+                    None => ret.bound = Some(custom_clauses.into_iter().collect()),
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(ret)
+    }
+
+    fn name(&self, ident: &Ident) -> String {
+        match &self.name {
+            Some(name) => name.value(),
+            None => ident.to_string(),
+        }
+    }
+
+    /// The name of a field or variant after `rename_all`.
+    fn rename(&self, ident: &Ident) -> String {
+        let name = ident.unraw().to_string();
+        match &self.rename_all {
+            Some(rule) => rule.apply(&name),
+            None => name,
+        }
+    }
+}
+
+/// Case conversions accepted by `#[debug(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    const ALL: [(&'static str, RenameRule); 8] = [
+        ("lowercase", RenameRule::Lower),
+        ("UPPERCASE", RenameRule::Upper),
+        ("PascalCase", RenameRule::Pascal),
+        ("camelCase", RenameRule::Camel),
+        ("snake_case", RenameRule::Snake),
+        ("SCREAMING_SNAKE_CASE", RenameRule::ScreamingSnake),
+        ("kebab-case", RenameRule::Kebab),
+        ("SCREAMING-KEBAB-CASE", RenameRule::ScreamingKebab),
+    ];
+
+    fn parse(lit: &LitStr) -> Result<Self> {
+        let value = lit.value();
+        match Self::ALL.iter().find(|(name, _)| *name == value) {
+            Some((_, rule)) => Ok(*rule),
+            None => {
+                let names: Vec<_> = Self::ALL
+                    .iter()
+                    .map(|(name, _)| format!("`{}`", name))
+                    .collect();
+                Err(Error::new_spanned(
+                    lit,
+                    format!(
+                        "unknown `rename_all` case, expected one of {}",
+                        names.join(", ")
+                    ),
+                ))
+            }
+        }
+    }
+
+    // 字段是 snake_case，变体是 PascalCase，先统一拆成单词再拼接
+    fn apply(self, name: &str) -> String {
+        let words = split_words(name);
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        };
+        let join = |f: &dyn Fn(&str) -> String, sep: &str| {
+            words
+                .iter()
+                .map(|word| f(word))
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+
+        match self {
+            RenameRule::Lower => join(&|word| word.to_lowercase(), ""),
+            RenameRule::Upper => join(&|word| word.to_uppercase(), ""),
+            RenameRule::Pascal => join(&capitalize, ""),
+            RenameRule::Camel => {
+                let pascal = join(&capitalize, "");
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => pascal,
+                }
+            }
+            RenameRule::Snake => join(&|word| word.to_lowercase(), "_"),
+            RenameRule::ScreamingSnake => join(&|word| word.to_uppercase(), "_"),
+            RenameRule::Kebab => join(&|word| word.to_lowercase(), "-"),
+            RenameRule::ScreamingKebab => join(&|word| word.to_uppercase(), "-"),
+        }
+    }
+}
+
+// 在 `_` 处和大小写变化处断开：user_id => [user, id]，HTTPServer => [HTTP, Server]
+fn split_words(name: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = name.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if start < offset {
+                words.push(&name[start..offset]);
+            }
+            start = offset + 1;
+            continue;
+        }
+        if i == 0 || !c.is_uppercase() || start == offset {
+            continue;
+        }
+        let prev = chars[i - 1].1;
+        let next_is_lower = chars
+            .get(i + 1)
+            .is_some_and(|&(_, next)| next.is_lowercase());
+        if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+            words.push(&name[start..offset]);
+            start = offset;
+        }
+    }
+    if start < name.len() {
+        words.push(&name[start..]);
+    }
+    words
+}
+
+/// Whether a field is printed.
+enum Skip {
+    Never,
+    /// `#[debug(skip)]`
+    Always,
+    /// `#[debug(skip_if = "path")]`, with `path: fn(&T) -> bool`.
+    If(Path),
+}
+
+/// How a `#[debug(redact)]` field hides its value.
+enum Redact {
+    /// `#[debug(redact)]`: `<redacted>`.
+    Full,
+    /// `#[debug(redact = "partial")]`: `<redacted: 12 chars>`, or with
+    /// `last = N` the last N characters as `<redacted: ...abcd>`. The field
+    /// must implement `AsRef<str>`. `last = 0` is parsed as `Full`.
+    Partial { last: Option<usize> },
+}
+
+/// A `#[debug = "..."]` format string. Besides the field itself as the
+/// positional argument it may name sibling fields, `{timeout}`, or call
+/// methods on `self`, `{self.method()}`.
+struct FmtArg {
+    /// The format string with every named placeholder renamed to `__argN`.
+    format: String,
+    /// What `__arg0`, `__arg1`, ... refer to.
+    args: Vec<Placeholder>,
+}
+
+enum Placeholder {
+    /// A sibling field, with the placeholder's span inside the literal.
+    Field(String, Span),
+    /// `self` followed by field accesses and calls without arguments.
+    SelfExpr(TokenStream2),
+}
+
+impl FmtArg {
+    // 在宏里解析一遍格式字符串，错误指向字面量而不是 derive：
+    //     括号必须成对，字面量的括号写成 {{ 和 }}
+    //     唯一的位置参数是字段本身，{} 和 {0} 都指向它，并且必须用到
+    //     格式说明符按 std::fmt 的语法检查，宽度和精度可以引用其他字段 {:width$}
+    fn parse(lit: &LitStr) -> Result<Self> {
+        let value = lit.value();
+        let mut parser = FmtParser {
+            lit,
+            format: String::new(),
+            args: Vec::new(),
+            next_index: 0,
+            uses_field: false,
+            errors: None,
+        };
+
+        let mut pos = 0;
+        while pos < value.len() {
+            let rest = &value[pos..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                parser.format.push_str(&rest[..2]);
+                pos += 2;
+            } else if let Some(inner) = rest.strip_prefix('{') {
+                match inner.find(['{', '}']) {
+                    Some(len) if inner[len..].starts_with('}') => {
+                        parser.placeholder(&value, pos + 1..pos + 1 + len);
+                        pos += len + 2;
+                    }
+                    _ => {
+                        parser.error(
+                            pos..pos + 1,
+                            "unclosed `{` in format string, use `{{` for a literal brace",
+                        );
+                        break;
+                    }
+                }
+            } else if rest.starts_with('}') {
+                parser.error(
+                    pos..pos + 1,
+                    "unmatched `}` in format string, use `}}` for a literal brace",
+                );
+                pos += 1;
+            } else {
+                let c = rest.chars().next().unwrap();
+                parser.format.push(c);
+                pos += c.len_utf8();
+            }
+        }
+
+        if !parser.uses_field && parser.errors.is_none() {
+            parser.errors = Some(Error::new_spanned(
+                lit,
+                "format string does not print the field, add `{}`",
+            ));
+        }
+        match parser.errors {
+            Some(errors) => Err(errors),
+            None => Ok(FmtArg {
+                format: parser.format,
+                args: parser.args,
+            }),
+        }
+    }
+
+    /// The sibling fields named by the format string.
+    fn fields(&self) -> impl Iterator<Item = &str> {
+        self.args.iter().filter_map(|arg| match arg {
+            Placeholder::Field(name, _) => Some(name.as_str()),
+            Placeholder::SelfExpr(_) => None,
+        })
+    }
+}
+
+/// Format traits accepted after the `:` of a placeholder.
+const FORMAT_TRAITS: &[&str] = &["", "?", "x?", "X?", "o", "x", "X", "p", "b", "e", "E"];
+
+struct FmtParser<'a> {
+    lit: &'a LitStr,
+    /// The format string with every named placeholder renamed to `__argN`.
+    format: String,
+    args: Vec<Placeholder>,
+    /// The positional argument the next `{}` refers to.
+    next_index: usize,
+    uses_field: bool,
+    errors: Option<Error>,
+}
+
+impl FmtParser<'_> {
+    fn error(&mut self, range: Range<usize>, message: impl Display) {
+        let span = lit_subspan(self.lit, range);
+        push_error(&mut self.errors, Error::new(span, message));
+    }
+
+    // range 是 `{` 和 `}` 之间的内容在格式字符串中的位置
+    fn placeholder(&mut self, value: &str, range: Range<usize>) {
+        let inner = &value[range.clone()];
+        let (arg, spec) = match inner.split_once(':') {
+            Some((arg, spec)) => (arg, Some(spec)),
+            None => (inner, None),
+        };
+        let arg_range = range.start..range.start + arg.len();
+
+        self.format.push('{');
+        if arg.is_empty() {
+            let index = self.next_index;
+            self.next_index += 1;
+            // 隐式的 `{}` 没有参数可指，报错时指向整个占位符
+            self.positional(index, range.start - 1..range.end + 1);
+        } else if let Ok(index) = arg.parse::<usize>() {
+            self.positional(index, arg_range);
+            self.format.push_str(arg);
+        } else if arg == "self" || arg.starts_with("self.") {
+            let span = lit_subspan(self.lit, arg_range);
+            match parse_self_expr(arg, span) {
+                Ok(expr) => self.named(Placeholder::SelfExpr(expr)),
+                Err(error) => push_error(&mut self.errors, error),
+            }
+        } else if syn::parse_str::<Ident>(arg).is_ok() {
+            let span = lit_subspan(self.lit, arg_range);
+            self.named(Placeholder::Field(arg.to_owned(), span));
+        } else {
+            self.error(
+                arg_range,
+                format!(
+                    "expected a field name or `self.method()` in format string, found `{}`",
+                    arg,
+                ),
+            );
+        }
+        if let Some(spec) = spec {
+            self.format.push(':');
+            self.spec(spec, range.start + arg.len() + 1);
+        }
+        self.format.push('}');
+    }
+
+    fn positional(&mut self, index: usize, range: Range<usize>) {
+        if index == 0 {
+            self.uses_field = true;
+        } else {
+            self.error(
+                range,
+                format!(
+                    "format string refers to positional argument {}, but the field is the only positional argument",
+                    index,
+                ),
+            );
+        }
+    }
+
+    fn named(&mut self, placeholder: Placeholder) {
+        self.format.push_str(&format!("__arg{}", self.args.len()));
+        self.args.push(placeholder);
+    }
+
+    // format_spec := [[fill]align][sign]['#']['0'][width]['.' precision]type
+    fn spec(&mut self, spec: &str, offset: usize) {
+        let mut rest = spec;
+        let mut chars = rest.chars();
+        let flags = match (chars.next(), chars.next()) {
+            (Some(fill), Some('<' | '^' | '>')) => fill.len_utf8() + 1,
+            (Some('<' | '^' | '>'), _) => 1,
+            _ => 0,
+        };
+        rest = &rest[flags..];
+        for flag in ["+", "-", "#"] {
+            rest = rest.strip_prefix(flag).unwrap_or(rest);
+        }
+        // {:0$} 的 0 是位置参数，不是补零
+        if rest.starts_with('0') && !rest[1..].starts_with('$') {
+            rest = &rest[1..];
+        }
+        self.format.push_str(&spec[..spec.len() - rest.len()]);
+
+        rest = self.count(rest, offset + spec.len() - rest.len());
+        if let Some(precision) = rest.strip_prefix('.') {
+            self.format.push('.');
+            let start = offset + spec.len() - precision.len();
+            rest = if let Some(after) = precision.strip_prefix('*') {
+                self.error(
+                    start..start + 1,
+                    "`.*` takes an extra positional argument, name a field instead as in `.precision$`",
+                );
+                after
+            } else {
+                let after = self.count(precision, start);
+                if after.len() == precision.len() {
+                    self.error(start - 1..start, "expected a precision after `.`");
+                }
+                after
+            };
+        }
+
+        if FORMAT_TRAITS.contains(&rest) {
+            self.format.push_str(rest);
+        } else {
+            let start = offset + spec.len() - rest.len();
+            self.error(
+                start..offset + spec.len(),
+                format!("unknown format trait `{}`", rest),
+            );
+        }
+    }
+
+    // count := integer | integer '$' | identifier '$'，返回剩下的部分
+    fn count<'s>(&mut self, rest: &'s str, offset: usize) -> &'s str {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits > 0 {
+            let after = &rest[digits..];
+            if let Some(after) = after.strip_prefix('$') {
+                let index = rest[..digits].parse().unwrap_or(usize::MAX);
+                self.positional(index, offset..offset + digits);
+                self.format.push_str(&rest[..=digits]);
+                return after;
+            }
+            self.format.push_str(&rest[..digits]);
+            return after;
+        }
+
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| !(c == '_' || c.is_alphabetic() || (i > 0 && c.is_alphanumeric())))
+            .map_or(rest.len(), |(i, _)| i);
+        match rest[len..].strip_prefix('$') {
+            Some(after) if len > 0 => {
+                let name = &rest[..len];
+                let span = lit_subspan(self.lit, offset..offset + len);
+                self.named(Placeholder::Field(name.to_owned(), span));
+                self.format.push('$');
+                after
+            }
+            // 不是参数，剩下的是格式 trait，例如 {:x}
+            _ => rest,
+        }
+    }
+}
+
+// 把错误累积起来，一次编译报告所有的问题
+fn push_error(errors: &mut Option<Error>, error: Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+// 只接受 self.a.b() 这样的写法：字段访问和不带参数的方法调用
+fn parse_self_expr(arg: &str, span: Span) -> Result<TokenStream2> {
+    let mut segments = arg.split('.');
+    segments.next();
+
+    let mut expr = quote_spanned!(span=> self);
+    for segment in segments {
+        let (name, call) = match segment.strip_suffix("()") {
+            Some(name) => (name, true),
+            None => (segment, false),
+        };
+        let member = match (name.parse::<u32>(), syn::parse_str::<Ident>(name)) {
+            (Ok(index), _) if !call => Member::Unnamed(syn::Index { index, span }),
+            (_, Ok(mut ident)) => {
+                ident.set_span(span);
+                Member::Named(ident)
+            }
+            _ => {
+                return Err(Error::new(
+                    span,
+                    format!("expected `self.field` or `self.method()`, found `{}`", arg),
+                ))
+            }
+        };
+        let call = call.then(|| quote_spanned!(span=> ()));
+        expr = quote_spanned!(span=> #expr.#member #call);
+    }
+    Ok(expr)
+}
+
+// 给出格式字符串中一段内容的位置。Literal::subspan 目前只在 nightly 上可用，
+// 其余情况以及含有转义的字符串退回到整个字面量
+fn lit_subspan(lit: &LitStr, range: Range<usize>) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    let offset = match repr.strip_prefix('r') {
+        Some(raw) => raw.find('"').map(|hashes| hashes + 2),
+        None if repr.len() == lit.value().len() + 2 && !repr.contains('\\') => Some(1),
+        None => None,
+    };
+    offset
+        .and_then(|offset| token.subspan(range.start + offset..range.end + offset))
+        .unwrap_or_else(|| lit.span())
+}
+
+/// Shorthands for common field formats.
+enum Preset {
+    /// `#[debug(hex)]`: `0xff`. With `hex(width = N)` the digits are padded
+    /// with zeros to N, `0x000000ff`.
+    Hex { width: Option<usize> },
+    /// `#[debug(binary)]`: `0b101`.
+    Binary,
+    /// `#[debug(ptr)]`: only the address a reference or smart pointer
+    /// points to.
+    Ptr,
+    /// `#[debug(len)]`: only the collection's `.len()`.
+    Len,
+    /// `#[debug(truncate = N)]`: at most the first N characters of a string,
+    /// followed by `...` when cut. The field must implement `AsRef<str>`.
+    Truncate(usize),
+    /// `#[debug(max_items = N)]`: the first N items, followed by
+    /// `... (M more)`. Works with anything whose reference is `IntoIterator`.
+    MaxItems(usize),
+}
+
+impl Preset {
+    const NAMES: [&'static str; 6] = ["hex", "binary", "ptr", "len", "truncate", "max_items"];
+
+    fn parse(nested: &ParseNestedMeta) -> Result<Self> {
+        let path = &nested.path;
+        if path.is_ident("hex") {
+            let mut width = None;
+            if nested.input.peek(token::Paren) {
+                nested.parse_nested_meta(|inner| {
+                    if !inner.path.is_ident("width") {
+                        return Err(inner.error("expected `hex(width = N)`"));
+                    }
+                    let lit: LitInt = inner.value()?.parse()?;
+                    width = Some(lit.base10_parse()?);
+                    Ok(())
+                })?;
+            }
+            Ok(Preset::Hex { width })
+        } else if path.is_ident("binary") {
+            Ok(Preset::Binary)
+        } else if path.is_ident("ptr") {
+            Ok(Preset::Ptr)
+        } else if path.is_ident("len") {
+            Ok(Preset::Len)
+        } else {
+            let lit: LitInt = nested.value()?.parse()?;
+            let count = lit.base10_parse()?;
+            if path.is_ident("truncate") {
+                Ok(Preset::Truncate(count))
+            } else {
+                Ok(Preset::MaxItems(count))
+            }
+        }
+    }
+}
+
+struct DebugField {
+    /// The field's name, or its index for tuple fields.
+    member: Member,
+    /// The name printed for the field, `None` for tuple fields.
+    name: Option<String>,
+    /// Name of the field's binding when matching on an enum variant.
+    binding: Ident,
+    ty: Type,
+    fmt_arg: Option<FmtArg>,
+    skip: Skip,
+    /// Always `None` with the `unredacted` feature.
+    redact: Option<Redact>,
+    /// `#[debug(with = "path")]`, with
+    /// `path: fn(&T, &mut fmt::Formatter) -> fmt::Result`.
+    with: Option<Path>,
+    /// `#[debug(flatten)]`: the field's own fields are printed inline through
+    /// `derive_debug::DebugFields`.
+    flatten: bool,
+    preset: Option<Preset>,
+    /// `#[debug(bound = "...")]`: replaces the bounds inferred from this
+    /// field, and applies even with a container-level bound.
+    bound: Option<Vec<WherePredicate>>,
+}
+
+impl DebugField {
+    fn from_fields<'a>(
+        fields: impl Iterator<Item = &'a Field>,
+        container: &ContainerAttrs,
+    ) -> Result<Vec<Self>> {
+        let fields: Vec<&Field> = fields.collect();
+        let mut errors = None::<Error>;
+        let mut debug_fields = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            match DebugField::try_from(index, field, container) {
+                Ok(debug_field) => debug_fields.push(debug_field),
+                Err(error) => push_error(&mut errors, error),
+            }
+        }
+
+        // 格式字符串引用的字段必须在同一个结构体或者变体中
+        let field_names: Set<String> = fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref())
+            .map(|ident| ident.unraw().to_string())
+            .collect();
+        for fmt_arg in debug_fields
+            .iter()
+            .filter_map(|field| field.fmt_arg.as_ref())
+        {
+            for arg in &fmt_arg.args {
+                let (name, span) = match arg {
+                    Placeholder::Field(name, span) => (name, *span),
+                    Placeholder::SelfExpr(_) => continue,
+                };
+                if !field_names.contains(name) {
+                    let message = format!("no field `{}` to use in format string", name);
+                    push_error(&mut errors, Error::new(span, message));
+                }
+            }
+        }
+
+        match errors {
+            Some(errors) => Err(errors),
+            None => Ok(debug_fields),
+        }
+    }
+
+    /// Whether printing the field needs `Debug` for its type.
+    fn uses_debug(&self) -> bool {
+        // 跳过、隐藏、自定义格式化函数、展开的字段和大部分预设格式都不会用到 Debug
+        !matches!(self.skip, Skip::Always)
+            && self.redact.is_none()
+            && self.with.is_none()
+            && !self.flatten
+            && matches!(self.preset, None | Some(Preset::MaxItems(_)))
+            && self.bound.is_none()
+    }
+
+    /// The trait the whole field type needs when it is not `Debug`.
+    fn field_bound(&self) -> Option<TokenStream2> {
+        if matches!(self.skip, Skip::Always) || self.bound.is_some() {
+            return None;
+        }
+        if self.flatten {
+            return Some(quote!(::derive_debug::DebugFields));
+        }
+        if let Some(Redact::Partial { .. }) = self.redact {
+            return Some(quote!(std::convert::AsRef<str>));
+        }
+        match self.preset.as_ref()? {
+            Preset::Hex { .. } => Some(quote!(std::fmt::LowerHex)),
+            Preset::Binary => Some(quote!(std::fmt::Binary)),
+            Preset::Ptr => Some(quote!(std::fmt::Pointer)),
+            Preset::Truncate(_) => Some(quote!(std::convert::AsRef<str>)),
+            Preset::Len | Preset::MaxItems(_) => None,
+        }
+    }
+
+    /// The field's Rust name without `r#`, empty for tuple fields.
+    fn ident_name(&self) -> String {
+        match &self.member {
+            Member::Named(ident) => ident.unraw().to_string(),
+            Member::Unnamed(_) => String::new(),
+        }
+    }
+
+    // 支持的写法：
+    //     #[debug = "..."]
+    //     #[debug(skip)]
+    //     #[debug(skip_if = "path::to_fn")]
+    //     #[debug(redact)] / #[debug(redact = "partial")] / #[debug(redact = "partial", last = N)]
+    //     #[debug(with = "path::to_fn")]
+    //     #[debug(rename = "...")]
+    //     #[debug(flatten)]
+    //     #[debug(hex)] / #[debug(hex(width = N))] / #[debug(binary)] / #[debug(ptr)]
+    //     #[debug(len)] / #[debug(truncate = N)] / #[debug(max_items = N)]
+    //     #[debug(bound = "T: Trait")]
+    fn try_from(index: usize, field: &Field, container: &ContainerAttrs) -> Result<Self> {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let ty = field.ty.clone();
+        let mut fmt_arg = None::<FmtArg>;
+        let mut skip = Skip::Never;
+        let mut redact = None::<Redact>;
+        let mut last = None::<LitInt>;
+        let mut with = None::<(LitStr, Path)>;
+        let mut rename = None::<LitStr>;
+        let mut flatten = None::<Path>;
+        let mut preset = None::<(Path, Preset)>;
+        let mut bound = None::<Vec<WherePredicate>>;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("debug") {
+                continue;
+            }
+
+            let expected = r#"expected `debug = "..."`, `debug(skip)`, `debug(skip_if = "...")`, `debug(redact)`, `debug(with = "...")`, `debug(rename = "...")`, `debug(flatten)`, `debug(bound = "...")` or a preset such as `debug(hex)`"#;
+            let meta = match &attr.meta {
+                Meta::NameValue(meta) => meta,
+                Meta::List(meta) => {
+                    meta.parse_nested_meta(|nested| {
+                        if nested.path.is_ident("skip") {
+                            skip = Skip::Always;
+                        } else if nested.path.is_ident("skip_if") {
+                            let lit: LitStr = nested.value()?.parse()?;
+                            skip = Skip::If(lit.parse()?);
+                        } else if nested.path.is_ident("redact") {
+                            if nested.input.peek(Token![=]) {
+                                let lit: LitStr = nested.value()?.parse()?;
+                                if lit.value() != "partial" {
+                                    return Err(Error::new_spanned(
+                                        lit,
+                                        r#"expected `redact = "partial"`"#,
+                                    ));
+                                }
+                                redact = Some(Redact::Partial { last: None });
+                            } else {
+                                redact = Some(Redact::Full);
+                            }
+                        } else if nested.path.is_ident("with") {
+                            let lit: LitStr = nested.value()?.parse()?;
+                            let path = lit.parse()?;
+                            with = Some((lit, path));
+                        } else if nested.path.is_ident("flatten") {
+                            flatten = Some(nested.path.clone());
+                        } else if nested.path.is_ident("bound") {
+                            let lit: LitStr = nested.value()?.parse()?;
+                            let clauses =
+                                lit.parse_with(Punctuated::<_, Token![,]>::parse_terminated)?;
+                            bound.get_or_insert_with(Vec::new).extend(clauses);
+                        } else if nested.path.is_ident("rename") {
+                            rename = Some(nested.value()?.parse()?);
+                        } else if Preset::NAMES.iter().any(|name| nested.path.is_ident(name)) {
+                            if preset.is_some() {
+                                return Err(nested.error("only one format preset per field"));
+                            }
+                            preset = Some((nested.path.clone(), Preset::parse(&nested)?));
+                        } else if nested.path.is_ident("last") {
+                            let lit: LitInt = nested.value()?.parse()?;
+                            lit.base10_parse::<usize>()?;
+                            last = Some(lit);
+                        } else {
+                            return Err(Error::new_spanned(&nested.path, expected));
+                        }
+                        Ok(())
+                    })?;
+                    continue;
+                }
+                meta => return Err(Error::new_spanned(meta, expected)),
+            };
+
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(ref lit),
+                ..
+            }) = &meta.value
+            {
+                fmt_arg = Some(FmtArg::parse(lit)?);
+            } else {
+                return Err(Error::new_spanned(meta, expected));
+            }
+        }
+
+        if let Some(lit) = last {
+            match &mut redact {
+                // 不露出任何字符就是完全隐藏
+                Some(redact @ Redact::Partial { .. }) if lit.base10_parse::<usize>()? == 0 => {
+                    *redact = Redact::Full
+                }
+                Some(Redact::Partial { last }) => *last = Some(lit.base10_parse()?),
+                _ => {
+                    return Err(Error::new_spanned(
+                        lit,
+                        r#"`last` requires `redact = "partial"`"#,
+                    ))
+                }
+            }
+        }
+        if let Some((lit, _)) = &with {
+            if fmt_arg.is_some() || redact.is_some() {
+                return Err(Error::new_spanned(
+                    lit,
+                    r#"`with` cannot be combined with `debug = "..."` or `debug(redact)`"#,
+                ));
+            }
+        }
+        if let Some(path) = &flatten {
+            if field.ident.is_none() {
+                return Err(Error::new_spanned(
+                    path,
+                    "`flatten` only applies to named fields",
+                ));
+            }
+            if fmt_arg.is_some() || redact.is_some() || with.is_some() || rename.is_some() {
+                return Err(Error::new_spanned(
+                    path,
+                    r#"`flatten` cannot be combined with `debug = "..."`, `redact`, `with` or `rename`"#,
+                ));
+            }
+        }
+        if let Some((path, _)) = &preset {
+            if fmt_arg.is_some() || redact.is_some() || with.is_some() || flatten.is_some() {
+                return Err(Error::new_spanned(
+                    path,
+                    format!(
+                        r#"`{}` cannot be combined with `debug = "..."`, `redact`, `with` or `flatten`"#,
+                        path.get_ident().unwrap(),
+                    ),
+                ));
+            }
+        }
+        // 测试构建可以通过 unredacted 特性关掉隐藏，按普通字段输出
+        if cfg!(feature = "unredacted") {
+            redact = None;
+        }
+
+        let name = match (&member, rename) {
+            (Member::Named(_), Some(rename)) => Some(rename.value()),
+            (Member::Named(ident), None) => Some(container.rename(ident)),
+            (Member::Unnamed(_), Some(rename)) => {
+                return Err(Error::new_spanned(
+                    rename,
+                    "`rename` only applies to named fields",
+                ))
+            }
+            (Member::Unnamed(_), None) => None,
+        };
+        // 绑定名带上 mixed_site，不会遮蔽 fmt 的参数 f
+        let binding = match &member {
+            Member::Named(ident) => format_ident!("__{}", ident.unraw()),
+            Member::Unnamed(index) => format_ident!("__{}", index),
+        };
+
+        Ok(DebugField {
+            binding: Ident::new(&binding.to_string(), Span::mixed_site()),
+            member,
+            name,
+            ty,
+            fmt_arg,
+            skip,
+            redact,
+            with: with.map(|(_, path)| path),
+            flatten: flatten.is_some(),
+            preset: preset.map(|(_, preset)| preset),
+            bound,
+        })
+    }
+}
+
+// 找出字段类型中需要 Debug 约束的部分：类型参数本身 T，或者从它投影出来的
+// T::Value、<T as Trait>::Value 和 <u8 as Trait<T>>::Value。不论 T 是什么都实现了
+// Debug 的类型不需要约束：
+//     PhantomData<T>、fn(T) -> U、*const T
+// trait 对象 dyn Trait<T> 是否实现 Debug 也和 T 无关。生命周期和常量参数不需要约束，
+// 数组长度这样的常量表达式 [u8; size_of::<T>()] 也不会用到 T 的 Debug
+struct GenericVisitor<'ast> {
+    type_params: Vec<&'ast Ident>,
+    bounded_types: Set<&'ast TypePath>,
+}
+
+impl<'ast> GenericVisitor<'ast> {
+    fn get_used_type_params(
+        generics: &'ast Generics,
+        types: impl IntoIterator<Item = &'ast Type>,
+    ) -> Set<&'ast TypePath> {
+        let mut visitor = GenericVisitor {
+            type_params: generics.type_params().map(|param| &param.ident).collect(),
+            bounded_types: Set::new(),
+        };
+        for ty in types {
+            visitor.visit_type(ty);
+        }
+        visitor.bounded_types
+    }
+
+    // <Self as Trait<Args>>::Value 中 Self 和 Args 都可能用到类型参数
+    fn mentions_type_param(&self, ty: &'ast TypePath) -> bool {
+        let mut visitor = GenericVisitor {
+            type_params: self.type_params.clone(),
+            bounded_types: Set::new(),
+        };
+        if let Some(qself) = &ty.qself {
+            visitor.visit_type(&qself.ty);
+        }
+        visitor.visit_path(&ty.path);
+        !visitor.bounded_types.is_empty()
+    }
+}
+
+impl<'ast> Visit<'ast> for GenericVisitor<'ast> {
+    fn visit_type_path(&mut self, ty: &'ast TypePath) {
+        let path = &ty.path;
+
+        if ty.qself.is_some() {
+            if self.mentions_type_param(ty) {
+                self.bounded_types.insert(ty);
+            }
+            return;
+        }
+
+        if path.leading_colon.is_none() && self.type_params.contains(&&path.segments[0].ident) {
+            self.bounded_types.insert(ty);
+            return;
+        }
+
+        if !is_phantom_data(path) {
+            visit::visit_type_path(self, ty);
+        }
+    }
+
+    fn visit_type_bare_fn(&mut self, _: &'ast TypeBareFn) {}
+
+    fn visit_type_ptr(&mut self, _: &'ast TypePtr) {}
+
+    fn visit_type_trait_object(&mut self, _: &'ast TypeTraitObject) {}
+
+    fn visit_type_impl_trait(&mut self, _: &'ast TypeImplTrait) {}
+
+    fn visit_expr(&mut self, _: &'ast Expr) {}
+}
+
+// 声明中已经写了 T: Debug 的类型不再重复约束
+fn has_debug_bound(generics: &Generics, where_clause: &WhereClause, ty: &TypePath) -> bool {
+    let is_debug = |bound: &TypeParamBound| match bound {
+        TypeParamBound::Trait(bound) => bound.path.segments.last().unwrap().ident == "Debug",
+        _ => false,
+    };
+
+    let param_bounds = generics
+        .type_params()
+        .filter(|param| ty.qself.is_none() && ty.path.is_ident(&param.ident))
+        .flat_map(|param| &param.bounds);
+    let where_bounds = where_clause
+        .predicates
+        .iter()
+        .filter_map(|predicate| match predicate {
+            WherePredicate::Type(PredicateType {
+                bounded_ty: Type::Path(bounded_ty),
+                bounds,
+                ..
+            }) if bounded_ty == ty => Some(bounds),
+            _ => None,
+        })
+        .flatten();
+
+    param_bounds.chain(where_bounds).any(is_debug)
+}
+
+// PhantomData、marker::PhantomData、std::marker::PhantomData 或者 core::marker::PhantomData
+fn is_phantom_data(path: &Path) -> bool {
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    match segments.as_slice() {
+        [.., last] if last != "PhantomData" => false,
+        [_] => true,
+        [marker, _] => marker == "marker",
+        [krate, marker, _] => (krate == "std" || krate == "core") && marker == "marker",
+        _ => false,
+    }
+}
+
+fn make_field_calls(
+    fields: &[DebugField],
+    builder: &Ident,
+    hidden: Option<&Ident>,
+    place: impl Fn(&DebugField) -> TokenStream2,
+) -> TokenStream2 {
+    fields
+        .iter()
+        .map(|field| {
+            let fmt_args = field
+                .fmt_arg
+                .as_ref()
+                .map(|fmt_arg| make_fmt_args(fmt_arg, fields, &place));
+            let place = place(field);
+            // 元组字段没有名字，对应 debug_tuple 的 field(value)
+            let name = match &field.name {
+                Some(name) => quote!(#name,),
+                None => TokenStream2::new(),
+            };
+            // 字段没有实现 Debug 或者格式不匹配时，错误指向字段类型
+            let span = field.ty.span();
+
+            let call = match (&field.redact, &field.fmt_arg, &field.with) {
+                // 展开和预设格式的字段不会和其他格式组合，见 DebugField::try_from
+                _ if field.flatten => quote_spanned! {span=>
+                    ::derive_debug::DebugFields::debug_fields(&#place, #builder);
+                },
+                _ if field.preset.is_some() => {
+                    let preset = field.preset.as_ref().unwrap();
+                    make_preset_call(preset, &field.ty, builder, &name, &place)
+                }
+                (Some(redact), _, _) => make_redacted_call(redact, builder, &name, &place, span),
+                (None, Some(fmt_arg), _) => {
+                    let format = &fmt_arg.format;
+                    quote_spanned! {span=>
+                        #builder.field(#name &format_args!(#format, #place #fmt_args));
+                    }
+                }
+                // 函数签名不匹配时，错误指向属性里的路径
+                (None, None, Some(with)) => {
+                    let ty = &field.ty;
+                    let wrapper = Ident::new("__DebugWith", Span::mixed_site());
+                    let with = quote_spanned!(with.span()=> #with);
+                    quote_spanned!(span=> #builder.field(#name &#wrapper::<#ty>(&#place, #with));)
+                }
+                (None, None, None) => quote_spanned!(span=> #builder.field(#name &#place);),
+            };
+
+            match &field.skip {
+                Skip::Never => call,
+                Skip::Always => TokenStream2::new(),
+                Skip::If(predicate) => {
+                    let hide = hidden.map(|hidden| quote!(#hidden = true;));
+                    quote! {
+                        if #predicate(&#place) {
+                            #hide
+                        } else {
+                            #call
+                        }
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+// 格式字符串中的具名占位符对应 format_args! 的具名参数：, __arg0 = self.timeout
+fn make_fmt_args(
+    fmt_arg: &FmtArg,
+    fields: &[DebugField],
+    place: impl Fn(&DebugField) -> TokenStream2,
+) -> TokenStream2 {
+    fmt_arg
+        .args
+        .iter()
+        .enumerate()
+        .map(|(index, arg)| {
+            let arg_ident = format_ident!("__arg{}", index);
+            let value = match arg {
+                Placeholder::Field(name, _) => {
+                    // 在 DebugField::from_fields 中已经检查过字段存在
+                    let sibling = fields
+                        .iter()
+                        .find(|field| field.ident_name() == *name)
+                        .unwrap();
+                    place(sibling)
+                }
+                Placeholder::SelfExpr(expr) => expr.clone(),
+            };
+            quote!(, #arg_ident = #value)
+        })
+        .collect()
+}
+
+// 隐藏的字段不要求实现 Debug，部分隐藏只要求 AsRef<str>
+fn make_redacted_call(
+    redact: &Redact,
+    builder: &Ident,
+    name: &TokenStream2,
+    place: &TokenStream2,
+    span: Span,
+) -> TokenStream2 {
+    let redacted = Ident::new("redacted", Span::mixed_site());
+    let value = Ident::new("value", Span::mixed_site());
+    let count = Ident::new("count", Span::mixed_site());
+
+    let compute = match redact {
+        // 完全隐藏时输出是固定的，不必为它分配 String
+        Redact::Full => return quote!(#builder.field(#name &format_args!("<redacted>"));),
+        Redact::Partial { last: None } => quote_spanned! {span=>
+            format!("<redacted: {} chars>", std::convert::AsRef::<str>::as_ref(&#place).chars().count())
+        },
+        Redact::Partial { last: Some(last) } => quote_spanned! {span=> {
+            let #value: &str = std::convert::AsRef::<str>::as_ref(&#place);
+            let #count = #value.chars().count();
+            // 太短的值露出最后几个字符就等于全部露出，只显示长度
+            if #count > #last {
+                let (start, _) = #value.char_indices().nth(#count - #last).unwrap();
+                format!("<redacted: ...{}>", &#value[start..])
+            } else {
+                format!("<redacted: {} chars>", #count)
+            }
+        }},
+    };
+
+    quote! {{
+        let #redacted: std::string::String = #compute;
+        #builder.field(#name &format_args!("{}", #redacted));
+    }}
+}
+
+fn make_preset_call(
+    preset: &Preset,
+    ty: &Type,
+    builder: &Ident,
+    name: &TokenStream2,
+    place: &TokenStream2,
+) -> TokenStream2 {
+    let span = ty.span();
+    let format = match preset {
+        Preset::Hex { width: None } => "{:#x}".to_owned(),
+        Preset::Hex { width: Some(width) } => format!("0x{{:0{}x}}", width),
+        Preset::Binary => "{:#b}".to_owned(),
+        Preset::Ptr => "{:p}".to_owned(),
+        Preset::Len => return quote_spanned!(span=> #builder.field(#name &(#place).len());),
+        Preset::Truncate(max) => {
+            let value = Ident::new("value", Span::mixed_site());
+            let end = Ident::new("end", Span::mixed_site());
+            return quote_spanned! {span=> {
+                let #value: &str = std::convert::AsRef::<str>::as_ref(&#place);
+                match #value.char_indices().nth(#max) {
+                    Some((#end, _)) => #builder.field(#name &format_args!("{:?}...", &#value[..#end])),
+                    None => #builder.field(#name &#value),
+                };
+            }};
+        }
+        // 通过 (&place).into_iter() 的方法调用自动解引用，Vec、切片的引用和映射都可以
+        Preset::MaxItems(max) => {
+            let items = quote_spanned!(span=> || (&#place).into_iter());
+            let wrapper = match collection_kind(ty) {
+                Some("map") => {
+                    let wrapper = Ident::new("__DebugMapItems", Span::mixed_site());
+                    quote!(#wrapper(#items, #max))
+                }
+                kind => {
+                    let wrapper = Ident::new("__DebugItems", Span::mixed_site());
+                    let set = kind == Some("set");
+                    quote!(#wrapper(#items, #max, #set))
+                }
+            };
+            return quote_spanned!(span=> #builder.field(#name &#wrapper););
+        }
+    };
+    quote_spanned!(span=> #builder.field(#name &format_args!(#format, #place));)
+}
+
+// 映射按 {key: value, ...}、集合按 {item, ...} 的形式输出，只能从类型的名字判断
+fn collection_kind(ty: &Type) -> Option<&'static str> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            let last = &path.segments.last().unwrap().ident;
+            if last == "HashMap" || last == "BTreeMap" {
+                Some("map")
+            } else if last == "HashSet" || last == "BTreeSet" {
+                Some("set")
+            } else {
+                None
+            }
+        }
+        Type::Reference(reference) => collection_kind(&reference.elem),
+        _ => None,
+    }
+}
+
+// 各种输出方式用到的包装类型，只生成用到的
+fn make_wrappers(fields: &[DebugField]) -> TokenStream2 {
+    let mut wrappers = TokenStream2::new();
+    if fields.iter().any(|field| field.with.is_some()) {
+        wrappers.extend(make_with_wrapper());
+    }
+    if fields
+        .iter()
+        .any(|field| matches!(field.preset, Some(Preset::MaxItems(_))))
+    {
+        wrappers.extend(make_items_wrappers());
+    }
+    wrappers
+}
+
+// #[debug(max_items = N)] 的字段输出前 N 项，其余的只给出数量
+fn make_items_wrappers() -> TokenStream2 {
+    let items = Ident::new("__DebugItems", Span::mixed_site());
+    let map_items = Ident::new("__DebugMapItems", Span::mixed_site());
+    quote! {
+        // 第三个字段为 true 时按集合输出
+        struct #items<F>(F, usize, bool);
+
+        impl<F, I> std::fmt::Debug for #items<F>
+        where
+            F: Fn() -> I,
+            I: Iterator,
+            I::Item: std::fmt::Debug,
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let mut iter = (self.0)();
+                if self.2 {
+                    let mut set = f.debug_set();
+                    set.entries(iter.by_ref().take(self.1));
+                    let more = iter.count();
+                    if more > 0 {
+                        set.entry(&format_args!("... ({} more)", more));
+                    }
+                    set.finish()
+                } else {
+                    let mut list = f.debug_list();
+                    list.entries(iter.by_ref().take(self.1));
+                    let more = iter.count();
+                    if more > 0 {
+                        list.entry(&format_args!("... ({} more)", more));
+                    }
+                    list.finish()
+                }
+            }
+        }
+
+        struct #map_items<F>(F, usize);
+
+        impl<F, I, K, V> std::fmt::Debug for #map_items<F>
+        where
+            F: Fn() -> I,
+            I: Iterator<Item = (K, V)>,
+            K: std::fmt::Debug,
+            V: std::fmt::Debug,
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let alternate = f.alternate();
+                let mut iter = (self.0)();
+                let mut set = f.debug_set();
+                for (key, value) in iter.by_ref().take(self.1) {
+                    if alternate {
+                        set.entry(&format_args!("{:#?}: {:#?}", key, value));
+                    } else {
+                        set.entry(&format_args!("{:?}: {:?}", key, value));
+                    }
+                }
+                let more = iter.count();
+                if more > 0 {
+                    set.entry(&format_args!("... ({} more)", more));
+                }
+                set.finish()
+            }
+        }
+    }
+}
+
+// #[debug(with = "path")] 的字段经过这个包装类型输出，字段类型本身不需要实现 Debug
+fn make_with_wrapper() -> TokenStream2 {
+    let wrapper = Ident::new("__DebugWith", Span::mixed_site());
+    quote! {
+        struct #wrapper<'a, T: ?Sized>(
+            &'a T,
+            fn(&T, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+        );
+
+        impl<'a, T: ?Sized> std::fmt::Debug for #wrapper<'a, T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                (self.1)(self.0, f)
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// A field whose type does not implement the trait required by its format
+// is reported at the field's type instead of at `#[derive(CustomDebug)]`, and a
+// `debug(with = "...")` function with the wrong signature at its path.
+// Likewise a `debug(flatten)` field whose type does not implement
+// `DebugFields`, and a `debug(hex)` field whose type is not `LowerHex`.
+
+use derive_debug::CustomDebug;
+
+pub struct Opaque;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    opaque: Opaque,
+    #[debug = "0b{:08b}"]
+    ratio: f32,
+    #[debug(with = "fmt_flag")]
+    flag: u8,
+    #[debug(flatten)]
+    nested: Opaque,
+    #[debug(hex)]
+    label: &'static str,
+}
+
+fn fmt_flag(flag: &bool, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{}", flag)
+}
+
+fn main() {}
@@ -0,0 +1,66 @@
+// Enums print every variant the way `#[derive(Debug)]` would: unit variants
+// as their name, tuple variants through `debug_tuple` and struct variants
+// through `debug_struct`. Fields of variants accept `#[debug = "..."]`, and
+// the bounds of the impl are inferred from the fields of all variants, or
+// taken from `#[debug(bound = "...")]`.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub enum State<T> {
+    Idle,
+    Running(u32, #[debug = "{:.1}%"] f64),
+    Failed {
+        #[debug = "0x{:04x}"]
+        code: u16,
+        f: &'static str,
+    },
+    Done(T),
+    Marker(PhantomData<T>),
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug")]
+pub enum Lookup<T: Trait> {
+    Found { value: T::Value },
+    Missing,
+}
+
+struct NotDebug;
+
+impl Trait for NotDebug {
+    type Value = u8;
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_eq!(format!("{:?}", State::<u8>::Idle), "Idle");
+    assert_eq!(format!("{:?}", State::<u8>::Running(3, 42.25)), "Running(3, 42.2%)");
+    assert_eq!(
+        format!("{:?}", State::<u8>::Failed { code: 255, f: "disk" }),
+        r#"Failed { code: 0x00ff, f: "disk" }"#,
+    );
+    assert_eq!(format!("{:?}", State::Done("ok")), r#"Done("ok")"#);
+    assert_eq!(
+        format!("{:#?}", State::<u8>::Running(1, 0.0)),
+        "Running(\n    1,\n    0.0%,\n)",
+    );
+
+    assert_debug::<State<PhantomData<NotDebug>>>();
+    assert_debug::<Never>();
+    assert_debug::<Lookup<NotDebug>>();
+    assert_eq!(
+        format!("{:?}", Lookup::<NotDebug>::Found { value: 7 }),
+        "Found { value: 7 }",
+    );
+}
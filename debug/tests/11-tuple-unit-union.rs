@@ -0,0 +1,39 @@
+// Tuple structs print through `debug_tuple` and accept `#[debug = "..."]` on
+// their positional fields, unit structs print as their name. Unions cannot
+// tell which field is active, so they print opaquely as `Name { .. }` and
+// place no bound on their type parameters.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::mem::ManuallyDrop;
+
+#[derive(CustomDebug)]
+pub struct Meters(#[debug = "{:.2}m"] f64);
+
+#[derive(CustomDebug)]
+pub struct Pair<T>(T, &'static str);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+#[derive(CustomDebug)]
+pub union Bits<T: Copy> {
+    int: u32,
+    float: f32,
+    other: ManuallyDrop<T>,
+}
+
+#[derive(Clone, Copy)]
+struct NotDebug;
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Meters(1.5)), "Meters(1.50m)");
+    assert_eq!(format!("{:?}", Pair(1, "one")), r#"Pair(1, "one")"#);
+    assert_eq!(format!("{:#?}", Pair((), "")), "Pair(\n    (),\n    \"\",\n)");
+    assert_eq!(format!("{:?}", Marker), "Marker");
+
+    assert_eq!(format!("{:?}", Bits::<u8> { int: 1 }), "Bits { .. }");
+    assert_debug::<Bits<NotDebug>>();
+}
@@ -0,0 +1,61 @@
+// `#[debug(skip)]` leaves a field out of the output and out of the inferred
+// bounds. `#[debug(skip_if = "path")]` leaves it out whenever `path(&field)`
+// returns true. With `#[debug(non_exhaustive)]` on the container the output
+// ends in `..` whenever a field was left out.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Connection<C> {
+    host: &'static str,
+    #[debug(skip)]
+    cache: C,
+    #[debug(skip_if = "Option::is_none")]
+    user: Option<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub struct Request {
+    path: &'static str,
+    #[debug(skip_if = "Vec::is_empty")]
+    headers: Vec<(&'static str, &'static str)>,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub enum Handle {
+    File(#[debug(skip)] u64, &'static str),
+    Socket {
+        port: u16,
+        #[debug(skip_if = "is_zero")]
+        retries: u32,
+    },
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+struct NotDebug(#[allow(dead_code)] HashMap<u8, u8>);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let connection = Connection { host: "localhost", cache: NotDebug(HashMap::new()), user: None };
+    assert_eq!(format!("{:?}", connection), r#"Connection { host: "localhost" }"#);
+    let connection = Connection { user: Some("root"), ..connection };
+    assert_eq!(format!("{:?}", connection), r#"Connection { host: "localhost", user: Some("root") }"#);
+    assert_debug::<Connection<NotDebug>>();
+
+    let request = Request { path: "/", headers: vec![] };
+    assert_eq!(format!("{:?}", request), r#"Request { path: "/", .. }"#);
+    let request = Request { path: "/", headers: vec![("accept", "*/*")] };
+    assert_eq!(format!("{:?}", request), r#"Request { path: "/", headers: [("accept", "*/*")] }"#);
+
+    assert_eq!(format!("{:?}", Handle::File(3, "log")), r#"File("log", ..)"#);
+    assert_eq!(format!("{:?}", Handle::Socket { port: 80, retries: 0 }), "Socket { port: 80, .. }");
+    assert_eq!(format!("{:?}", Handle::Socket { port: 80, retries: 2 }), "Socket { port: 80, retries: 2 }");
+}
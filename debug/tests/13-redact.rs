@@ -0,0 +1,63 @@
+// `#[debug(redact)]` prints `<redacted>` in place of the value, so secrets
+// don't end up in logs. `#[debug(redact = "partial")]` prints only the length
+// of the value, and with `last = N` its last N characters; those fields must
+// implement `AsRef<str>`. `last = 0` shows nothing at all, like `redact`.
+// Redacted fields don't need to implement `Debug`, and a generic field with
+// partial redaction gets an inferred `AsRef<str>` bound instead.
+//
+// Building derive_debug with the `unredacted` feature prints them like any
+// other field instead, see 14-unredacted.rs.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct Key([u8; 32]);
+
+#[derive(CustomDebug)]
+pub struct Credentials<K> {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact)]
+    key: K,
+    #[debug(redact = "partial")]
+    session: String,
+    #[debug(redact = "partial", last = 4)]
+    card: &'static str,
+    #[debug(redact = "partial", last = 0)]
+    token: String,
+}
+
+#[derive(CustomDebug)]
+pub enum Auth {
+    Token(#[debug(redact = "partial", last = 4)] String),
+}
+
+#[derive(CustomDebug)]
+pub struct Tok<T> {
+    #[debug(redact = "partial")]
+    t: T,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let credentials = Credentials {
+        user: "root",
+        password: "hunter2".to_owned(),
+        key: Key([0; 32]),
+        session: "0123456789".to_owned(),
+        card: "4111111111111111",
+        token: "abc".to_owned(),
+    };
+    assert_eq!(
+        format!("{:?}", credentials),
+        r#"Credentials { user: "root", password: <redacted>, key: <redacted>, session: <redacted: 10 chars>, card: <redacted: ...1111>, token: <redacted> }"#,
+    );
+    assert_debug::<Credentials<Key>>();
+
+    assert_eq!(format!("{:?}", Auth::Token("abcdefgh".to_owned())), "Token(<redacted: ...efgh>)");
+    assert_eq!(format!("{:?}", Auth::Token("abc".to_owned())), "Token(<redacted: 3 chars>)");
+    assert_eq!(format!("{:?}", Auth::Token("ключ-секрет".to_owned())), "Token(<redacted: ...крет>)");
+    assert_eq!(format!("{:?}", Tok { t: "abcd" }), "Tok { t: <redacted: 4 chars> }");
+}
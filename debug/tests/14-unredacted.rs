@@ -0,0 +1,26 @@
+// With the `unredacted` feature of derive_debug, for example enabled from
+// `[dev-dependencies]` for test builds only, `#[debug(redact)]` has no effect
+// and the fields print normally, which requires them to implement `Debug`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Credentials {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "partial", last = 4)]
+    card: &'static str,
+}
+
+fn main() {
+    let credentials = Credentials {
+        user: "root",
+        password: "hunter2".to_owned(),
+        card: "4111111111111111",
+    };
+    assert_eq!(
+        format!("{:?}", credentials),
+        r#"Credentials { user: "root", password: "hunter2", card: "4111111111111111" }"#,
+    );
+}
@@ -0,0 +1,68 @@
+// `#[debug(with = "path")]` prints a field through a function with signature
+// `fn(&T, &mut fmt::Formatter) -> fmt::Result`. The field type does not need
+// to implement Debug, so it is also left out of the inferred bounds.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(CustomDebug)]
+pub struct Packet<C> {
+    #[debug(with = "hexdump")]
+    payload: Vec<u8>,
+    #[debug(with = "seconds_since_epoch")]
+    received: SystemTime,
+    #[debug(with = "Checksum::fmt_opaque")]
+    checksum: Checksum<C>,
+}
+
+pub struct Checksum<C>(u32, PhantomData<C>);
+
+impl<C> Checksum<C> {
+    fn fmt_opaque(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#010x}", self.0)
+    }
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    Tick(#[debug(with = "seconds_since_epoch")] SystemTime),
+    Data {
+        #[debug(skip_if = "Vec::is_empty", with = "hexdump")]
+        bytes: Vec<u8>,
+    },
+}
+
+fn hexdump(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+fn seconds_since_epoch(time: &SystemTime, f: &mut fmt::Formatter) -> fmt::Result {
+    let since = time.duration_since(UNIX_EPOCH).unwrap();
+    write!(f, "@{}", since.as_secs())
+}
+
+struct NotDebug;
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let packet = Packet::<NotDebug> {
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+        received: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        checksum: Checksum(0xbeef, PhantomData),
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { payload: deadbeef, received: @1700000000, checksum: 0x0000beef }",
+    );
+    assert_debug::<Packet<NotDebug>>();
+
+    assert_eq!(format!("{:?}", Event::Tick(UNIX_EPOCH)), "Tick(@0)");
+    assert_eq!(format!("{:?}", Event::Data { bytes: vec![1, 2] }), "Data { bytes: 0102 }");
+    assert_eq!(format!("{:?}", Event::Data { bytes: vec![] }), "Data");
+}
@@ -0,0 +1,71 @@
+// `#[debug(rename = "...")]` changes the name printed for a field or an enum
+// variant, `#[debug(name = "...")]` the name printed for a struct, and
+// `#[debug(rename_all = "...")]` converts the case of every field and variant
+// name. An explicit `rename` wins over `rename_all`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(name = "user", rename_all = "camelCase")]
+pub struct UserRecord {
+    user_id: u64,
+    display_name: &'static str,
+    #[debug(rename = "e-mail")]
+    email_address: &'static str,
+    r#type: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HttpStatus {
+    NotFound,
+    MovedPermanently { new_location: &'static str },
+    #[debug(rename = "I'm a teapot")]
+    ImATeapot,
+    HTTPVersionNotSupported(u8),
+}
+
+#[derive(CustomDebug)]
+#[debug(name = "Point")]
+pub struct RawPoint(i32, i32);
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "kebab-case")]
+pub struct Limits {
+    max_open_files: u32,
+    retry_after2_seconds: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "PascalCase")]
+pub enum Unchanged {
+    Ready { queue_len: usize },
+}
+
+fn main() {
+    let user = UserRecord {
+        user_id: 7,
+        display_name: "Ferris",
+        email_address: "ferris@example.com",
+        r#type: 1,
+    };
+    assert_eq!(
+        format!("{:?}", user),
+        r#"user { userId: 7, displayName: "Ferris", e-mail: "ferris@example.com", type: 1 }"#,
+    );
+
+    assert_eq!(format!("{:?}", HttpStatus::NotFound), "NOT_FOUND");
+    assert_eq!(
+        format!("{:?}", HttpStatus::MovedPermanently { new_location: "/" }),
+        r#"MOVED_PERMANENTLY { NEW_LOCATION: "/" }"#,
+    );
+    assert_eq!(format!("{:?}", HttpStatus::ImATeapot), "I'm a teapot");
+    assert_eq!(format!("{:?}", HttpStatus::HTTPVersionNotSupported(2)), "HTTP_VERSION_NOT_SUPPORTED(2)");
+
+    assert_eq!(format!("{:?}", RawPoint(1, 2)), "Point(1, 2)");
+
+    let limits = Limits { max_open_files: 1024, retry_after2_seconds: 3 };
+    assert_eq!(format!("{:?}", limits), "Limits { max-open-files: 1024, retry-after2-seconds: 3 }");
+
+    assert_eq!(format!("{:?}", Unchanged::Ready { queue_len: 0 }), "Ready { QueueLen: 0 }");
+}
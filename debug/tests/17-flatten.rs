@@ -0,0 +1,92 @@
+// `#[debug(flatten)]` prints the named fields of a field inline, as entries
+// of the parent's `debug_struct`, instead of nested inside their own braces.
+//
+// CustomDebug implements the companion trait `derive_debug::DebugFields` for
+// every struct with named fields, and a flattened field is printed through
+// it. Flattening works recursively, and a generic flattened field gets a
+// `FieldType: DebugFields` bound instead of `T: Debug`.
+
+use derive_debug::{CustomDebug, DebugFields};
+use std::fmt::{self, Debug};
+
+#[derive(CustomDebug)]
+pub struct Request<B> {
+    #[debug(flatten)]
+    meta: Meta,
+    #[debug(flatten)]
+    body: B,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "camelCase")]
+pub struct Meta {
+    method: &'static str,
+    #[debug(flatten)]
+    route: Route,
+    #[debug(skip_if = "Option::is_none")]
+    request_id: Option<u64>,
+}
+
+#[derive(CustomDebug)]
+pub struct Route {
+    path: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Body<T> {
+    #[debug = "{} bytes"]
+    length: usize,
+    content: T,
+}
+
+// Types other than CustomDebug structs can take part by implementing the
+// trait by hand.
+pub struct Empty;
+
+impl DebugFields for Empty {
+    fn debug_fields(&self, _builder: &mut fmt::DebugStruct) {}
+}
+
+#[derive(CustomDebug)]
+pub enum Message {
+    Request {
+        id: u32,
+        #[debug(flatten)]
+        meta: Meta,
+    },
+    Ping,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let meta = Meta { method: "GET", route: Route { path: "/" }, request_id: None };
+    assert_eq!(format!("{:?}", meta), r#"Meta { method: "GET", path: "/" }"#);
+
+    let request = Request { meta, body: Body { length: 2, content: "hi" } };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { method: "GET", path: "/", length: 2 bytes, content: "hi" }"#,
+    );
+
+    let request = Request {
+        meta: Meta { method: "HEAD", route: Route { path: "/x" }, request_id: Some(9) },
+        body: Empty,
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Request { method: "HEAD", path: "/x", requestId: Some(9) }"#,
+    );
+    assert_eq!(
+        format!("{:#?}", request),
+        "Request {\n    method: \"HEAD\",\n    path: \"/x\",\n    requestId: Some(\n        9,\n    ),\n}",
+    );
+    assert_debug::<Request<Empty>>();
+
+    let message = Message::Request {
+        id: 1,
+        meta: Meta { method: "GET", route: Route { path: "/" }, request_id: None },
+    };
+    assert_eq!(format!("{:?}", message), r#"Request { id: 1, method: "GET", path: "/" }"#);
+    assert_eq!(format!("{:?}", Message::Ping), "Ping");
+}
@@ -0,0 +1,100 @@
+// The format string in `#[debug = "..."]` can name sibling fields, which are
+// passed to `format_args!` as named arguments, and call methods on `self`.
+// The field itself stays the positional argument. Sibling fields can also
+// give the width and precision, `{:>width$.precision$}`.
+//
+//     #[debug = "{}ms (of {timeout}ms)"]
+//     elapsed: u64,
+//
+//     #[debug = "{} ({self.unit()})"]
+//     value: f64,
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Deadline {
+    #[debug = "{}ms (of {timeout}ms, {self.percent():.0}%)"]
+    elapsed: u64,
+    #[debug(skip)]
+    timeout: u64,
+}
+
+impl Deadline {
+    fn percent(&self) -> f64 {
+        self.elapsed as f64 * 100.0 / self.timeout as f64
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Reading {
+    #[debug = "{:.1}{self.unit.symbol()}"]
+    value: f64,
+    #[debug = "{{{}}} {self.unit.0:?}"]
+    unit: Unit,
+    #[debug = "{count:>3}/{}"]
+    total: u32,
+    count: u32,
+    #[debug = "[{:>width$.precision$}]"]
+    padded: f64,
+    width: usize,
+    precision: usize,
+}
+
+#[derive(Debug)]
+pub struct Unit(&'static str);
+
+impl Unit {
+    fn symbol(&self) -> &'static str {
+        if self.0 == "celsius" {
+            "°C"
+        } else {
+            "?"
+        }
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[derive(CustomDebug)]
+pub enum Transfer {
+    Progress {
+        #[debug = "{}/{total} bytes"]
+        done: u64,
+        #[debug(skip)]
+        total: u64,
+    },
+    Done(#[debug = "in {}s ({self.kind()})"] u32),
+}
+
+impl Transfer {
+    fn kind(&self) -> &'static str {
+        "transfer"
+    }
+}
+
+fn main() {
+    let deadline = Deadline { elapsed: 150, timeout: 200 };
+    assert_eq!(format!("{:?}", deadline), "Deadline { elapsed: 150ms (of 200ms, 75%) }");
+
+    let reading = Reading {
+        value: 21.56,
+        unit: Unit("celsius"),
+        total: 10,
+        count: 7,
+        padded: 1.5,
+        width: 6,
+        precision: 2,
+    };
+    assert_eq!(
+        format!("{:?}", reading),
+        r#"Reading { value: 21.6°C, unit: {celsius} "celsius", total:   7/10, count: 7, padded: [  1.50], width: 6, precision: 2 }"#,
+    );
+
+    let transfer = Transfer::Progress { done: 10, total: 40 };
+    assert_eq!(format!("{:?}", transfer), "Progress { done: 10/40 bytes }");
+    assert_eq!(format!("{:?}", Transfer::Done(3)), "Done(in 3s (transfer))");
+}
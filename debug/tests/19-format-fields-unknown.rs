@@ -0,0 +1,25 @@
+// A placeholder naming a field that does not exist in the same struct or
+// variant is an error at the placeholder, along with anything in `{...}` that
+// is neither a field name nor a `self.method()` call.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Deadline {
+    #[debug = "{}ms (of {timeot}ms)"]
+    elapsed: u64,
+    timeout: u64,
+}
+
+#[derive(CustomDebug)]
+pub enum Job {
+    Waiting {
+        #[debug = "{} behind {self.queue[0]}"]
+        position: usize,
+    },
+}
+
+#[derive(CustomDebug)]
+pub struct Done(#[debug = "{} of {total}"] usize, usize);
+
+fn main() {}
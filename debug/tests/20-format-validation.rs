@@ -0,0 +1,43 @@
+// The format string in `#[debug = "..."]` is parsed by the macro, so mistakes
+// are reported at the literal instead of as an error from inside the
+// generated `format_args!`, and all of them are reported in one pass:
+//
+//   - braces must be balanced, literal braces are written `{{` and `}}`;
+//   - the field is the only positional argument, so `{}` and `{0}` may refer
+//     to it but a second `{}` or `{1}` may not, and it must be printed;
+//   - format specs follow the `std::fmt` grammar.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Flags {
+    #[debug = "0b{:08x"]
+    bits: u8,
+    #[debug = "{} }"]
+    mask: u8,
+    #[debug = "{} and {}"]
+    pair: (u8, u8),
+    #[debug = "{1}"]
+    index: u8,
+    #[debug = "{:y}"]
+    unknown: u8,
+    #[debug = "{:.*}"]
+    precision: f32,
+    #[debug = "{:.}"]
+    empty_precision: f32,
+    #[debug = "{self.percent()}%"]
+    missing: u8,
+    #[debug = "{:>width$}"]
+    padded: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    Circle(#[debug = "r={:.2f}"] f64),
+    Square {
+        #[debug = "{side}"]
+        side: f64,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,117 @@
+// Shorthands for common field formats:
+//
+//   - `#[debug(hex)]` prints `0xff`, `#[debug(hex(width = 8))]` `0x000000ff`;
+//   - `#[debug(binary)]` prints `0b101`;
+//   - `#[debug(ptr)]` prints only the address of a reference or smart pointer;
+//   - `#[debug(len)]` prints only `.len()`;
+//   - `#[debug(truncate = N)]` prints at most N characters of a string;
+//   - `#[debug(max_items = N)]` prints the first N items of a collection, then
+//     `... (M more)`. Anything whose reference is `IntoIterator` works: `Vec`,
+//     slices, sets and maps.
+//
+// None of these need `Debug` for the field type, only for the items printed
+// by `max_items`.
+
+use derive_debug::CustomDebug;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+#[derive(CustomDebug)]
+pub struct Registers {
+    #[debug(hex)]
+    status: u8,
+    #[debug(hex(width = 8))]
+    address: u32,
+    #[debug(binary)]
+    flags: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Buffers<'a, T> {
+    #[debug(ptr)]
+    shared: Arc<String>,
+    #[debug(len)]
+    pending: Vec<T>,
+    #[debug(truncate = 5)]
+    title: String,
+    #[debug(truncate = 10)]
+    short: &'a str,
+}
+
+#[derive(CustomDebug)]
+pub struct Samples<'a> {
+    #[debug(max_items = 3)]
+    values: Vec<u32>,
+    #[debug(max_items = 2)]
+    window: &'a [u8],
+    #[debug(max_items = 2)]
+    tags: BTreeSet<&'static str>,
+    #[debug(max_items = 1)]
+    env: BTreeMap<&'static str, &'static str>,
+}
+
+#[derive(CustomDebug)]
+pub struct Env {
+    #[debug(max_items = 1)]
+    vars: BTreeMap<&'static str, &'static str>,
+}
+
+#[derive(CustomDebug)]
+pub enum Frame {
+    Data(#[debug(len)] Vec<u8>, #[debug(hex)] u16),
+    Names {
+        #[debug(max_items = 1)]
+        names: Vec<&'static str>,
+    },
+}
+
+struct NotDebug;
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let registers = Registers { status: 255, address: 0xbeef, flags: 5 };
+    assert_eq!(
+        format!("{:?}", registers),
+        "Registers { status: 0xff, address: 0x0000beef, flags: 0b101 }",
+    );
+
+    let shared = Arc::new("shared".to_owned());
+    let buffers = Buffers {
+        shared: shared.clone(),
+        pending: vec![NotDebug, NotDebug],
+        title: "Crème brûlée".to_owned(),
+        short: "tiny",
+    };
+    assert_eq!(
+        format!("{:?}", buffers),
+        format!(
+            r#"Buffers {{ shared: {:p}, pending: 2, title: "Crème"..., short: "tiny" }}"#,
+            shared,
+        ),
+    );
+    assert_debug::<Buffers<NotDebug>>();
+
+    let samples = Samples {
+        values: vec![1, 2, 3, 4, 5],
+        window: &[7, 8],
+        tags: ["a", "b", "c"].into_iter().collect(),
+        env: [("HOME", "/root"), ("LANG", "C")].into_iter().collect(),
+    };
+    assert_eq!(
+        format!("{:?}", samples),
+        r#"Samples { values: [1, 2, 3, ... (2 more)], window: [7, 8], tags: {"a", "b", ... (1 more)}, env: {"HOME": "/root", ... (1 more)} }"#,
+    );
+    let env = Env { vars: samples.env };
+    assert_eq!(
+        format!("{:#?}", env),
+        "Env {\n    vars: {\n        \"HOME\": \"/root\",\n        ... (1 more),\n    },\n}",
+    );
+
+    assert_eq!(format!("{:?}", Frame::Data(vec![0; 16], 0x1f)), "Data(16, 0x1f)");
+    assert_eq!(
+        format!("{:?}", Frame::Names { names: vec!["a", "b"] }),
+        r#"Names { names: ["a", ... (1 more)] }"#,
+    );
+}
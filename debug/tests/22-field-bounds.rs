@@ -0,0 +1,100 @@
+// A field-level `#[debug(bound = "...")]` replaces only the bounds inferred
+// from that field; the rest of the fields are still inferred.
+//
+// Inference only bounds the parts of a field type whose Debug impl depends on
+// a type parameter: the parameter itself, or a projection off it such as
+// `T::Value` or `<T as Trait>::Value`. Function pointers, raw pointers, trait
+// objects and PhantomData implement Debug (or not) regardless of the type
+// parameters they mention, so they add no bounds. Const generic parameters
+// never need a bound.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug, Display};
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Labeled<T, U> {
+    #[debug(bound = "T: Display")]
+    #[debug = "{}"]
+    label: T,
+    value: U,
+}
+
+#[derive(CustomDebug)]
+pub struct Projected<T: Trait> {
+    value: <T as Trait>::Value,
+    values: Vec<T::Value>,
+    marker: std::marker::PhantomData<T>,
+}
+
+pub trait Source<T>: Debug {
+    fn next(&mut self) -> Option<T>;
+}
+
+#[derive(CustomDebug)]
+pub struct Pipeline<'a, T, const N: usize> {
+    source: Box<dyn Source<T>>,
+    filter: fn(&T) -> bool,
+    last: *const T,
+    marker: PhantomData<&'a T>,
+    window: [u8; N],
+}
+
+#[derive(CustomDebug)]
+pub struct Grid<'a, T, const N: usize> {
+    cells: [T; N],
+    first: &'a T,
+}
+
+#[derive(Debug)]
+struct Counter(u32);
+
+impl<T> Source<T> for Counter {
+    fn next(&mut self) -> Option<T> {
+        None
+    }
+}
+
+struct Name(&'static str);
+
+impl Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+struct Id;
+
+impl Trait for Id {
+    type Value = u8;
+}
+
+struct NotDebug;
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Labeled<Name, u8>>();
+    let labeled = Labeled { label: Name("answer"), value: 42 };
+    assert_eq!(format!("{:?}", labeled), "Labeled { label: answer, value: 42 }");
+
+    assert_debug::<Projected<Id>>();
+    assert_debug::<Pipeline<'static, NotDebug, 4>>();
+    assert_debug::<Grid<'static, u8, 3>>();
+
+    let grid = Grid { cells: [1, 2, 3], first: &1 };
+    assert_eq!(format!("{:?}", grid), "Grid { cells: [1, 2, 3], first: 1 }");
+
+    let pipeline: Pipeline<NotDebug, 2> = Pipeline {
+        source: Box::new(Counter(0)),
+        filter: |_| true,
+        last: std::ptr::null(),
+        marker: PhantomData,
+        window: [0; 2],
+    };
+    assert!(format!("{:?}", pipeline).starts_with("Pipeline { source: Counter(0), filter: 0x"));
+}
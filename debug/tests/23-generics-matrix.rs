@@ -0,0 +1,122 @@
+// Bounds for structs that mix lifetimes, const generics and type parameters.
+// Only type parameters whose Debug impl is actually used get a bound, each at
+// most once, and a bound already written in the declaration is not repeated.
+//
+// For each shape below, the `assert_debug` calls instantiate every parameter
+// that should not be bounded with a type that does not implement Debug.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+
+pub trait Trait<T> {
+    type Value;
+}
+
+// A type parameter used only behind a reference to an array of const length.
+#[derive(CustomDebug)]
+pub struct Buf<'a, T, const N: usize> {
+    data: &'a [T; N],
+}
+
+// Defaults on type and const parameters.
+#[derive(CustomDebug)]
+pub struct Defaults<T = String, U = NotDebug, const N: usize = 2> {
+    value: T,
+    marker: PhantomData<U>,
+    array: [u8; N],
+}
+
+// Unsized parameters behind references and boxes.
+#[derive(CustomDebug)]
+pub struct Unsized<'a, T: ?Sized, U: ?Sized> {
+    borrowed: &'a T,
+    boxed: Box<U>,
+}
+
+// Lifetimes only, including one that only appears in PhantomData.
+#[derive(CustomDebug)]
+pub struct Lifetimes<'a, 'b: 'a> {
+    name: &'a str,
+    marker: PhantomData<&'b ()>,
+}
+
+// The same parameter used in several fields is bounded once, and a bound
+// already in the declaration or where clause is not repeated. (Repeating it
+// would still compile, so this case mostly guards against wrong bounds.)
+#[derive(CustomDebug)]
+pub struct Repeated<'a, T: Debug, U>
+where
+    U: fmt::Debug + Clone,
+{
+    first: T,
+    many: Vec<Vec<T>>,
+    maybe: Option<&'a T>,
+    other: U,
+}
+
+// Const generic arguments and const expressions mention no type parameters
+// that need Debug.
+#[derive(CustomDebug)]
+pub struct ConstExprs<T, const N: usize> {
+    bytes: [u8; std::mem::size_of::<u64>()],
+    nested: Buf<'static, u8, N>,
+    marker: PhantomData<T>,
+}
+
+// A projection through a trait generic over the parameter.
+#[derive(CustomDebug)]
+pub struct Projection<T> {
+    value: <u8 as Trait<T>>::Value,
+}
+
+// Tuple structs and enums follow the same rules.
+#[derive(CustomDebug)]
+pub struct Pair<'a, A, B: ?Sized>(&'a A, PhantomData<&'a B>);
+
+#[derive(CustomDebug)]
+pub enum Slot<'a, T, U, const N: usize> {
+    Empty,
+    Filled(&'a [T; N]),
+    Reserved { marker: PhantomData<U> },
+}
+
+pub struct NotDebug;
+
+impl<T> Trait<T> for u8 {
+    type Value = u16;
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Buf<u8, 4>>();
+    assert_debug::<Defaults>();
+    assert_debug::<Defaults<u8, NotDebug, 3>>();
+    assert_debug::<Unsized<str, [u8]>>();
+    assert_debug::<Lifetimes>();
+    assert_debug::<Repeated<u8, String>>();
+    assert_debug::<ConstExprs<NotDebug, 1>>();
+    assert_debug::<Projection<NotDebug>>();
+    assert_debug::<Pair<u8, NotDebug>>();
+    assert_debug::<Pair<u8, str>>();
+    assert_debug::<Slot<u8, NotDebug, 2>>();
+
+    let data = [1, 2];
+    assert_eq!(format!("{:?}", Buf { data: &data }), "Buf { data: [1, 2] }");
+
+    let defaults: Defaults = Defaults {
+        value: "x".to_owned(),
+        marker: PhantomData,
+        array: [0; 2],
+    };
+    let debug = format!("{:?}", defaults);
+    assert!(debug.starts_with(r#"Defaults { value: "x", marker: PhantomData<"#));
+    assert!(debug.ends_with("NotDebug>, array: [0, 0] }"));
+
+    let unsized_: Unsized<str, [u8]> = Unsized { borrowed: "s", boxed: Box::new([1]) };
+    assert_eq!(format!("{:?}", unsized_), r#"Unsized { borrowed: "s", boxed: [1] }"#);
+
+    let slot: Slot<u8, NotDebug, 2> = Slot::Filled(&data);
+    assert_eq!(format!("{:?}", slot), "Filled([1, 2])");
+}
@@ -9,4 +9,21 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.compile_fail("tests/09-field-diagnostics.rs");
+    t.pass("tests/10-enum.rs");
+    t.pass("tests/11-tuple-unit-union.rs");
+    t.pass("tests/12-skip.rs");
+    #[cfg(not(feature = "unredacted"))]
+    t.pass("tests/13-redact.rs");
+    #[cfg(feature = "unredacted")]
+    t.pass("tests/14-unredacted.rs");
+    t.pass("tests/15-with.rs");
+    t.pass("tests/16-rename.rs");
+    t.pass("tests/17-flatten.rs");
+    t.pass("tests/18-format-fields.rs");
+    t.compile_fail("tests/19-format-fields-unknown.rs");
+    t.compile_fail("tests/20-format-validation.rs");
+    t.pass("tests/21-presets.rs");
+    t.pass("tests/22-field-bounds.rs");
+    t.pass("tests/23-generics-matrix.rs");
 }